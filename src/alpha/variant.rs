@@ -1,26 +1,43 @@
 use crate::ast::Term;
 use crate::ast::AST;
-use crate::variables::VarMap;
+use crate::variables::{VarMap, VarSet};
 
 pub trait AlphaVariant {
     fn alpha_variant(&self) -> AST;
 }
 
 impl AST {
-    // Remap all varaibles in a term for different ones
-    fn remap(self, var_map: VarMap) -> AST {
+    // Remap all bound variables in a term for different ones. Free variables keep their
+    // names even if the same name is also binding somewhere else in the term
+    fn remap(self, var_map: VarMap, bound: &VarSet) -> AST {
         match self.term {
-            // Remap a variable - try to find a corresponding varaible in the map
+            // Remap a variable - try to find a corresponding varaible in the map if it is bound
+            // The type annotation of a parameter stays with it
+            Term::Var(ref s) if !bound.contains(&self.term) => AST {
+                annotation: self.annotation.clone(),
+                ..AST::var(s.clone())
+            },
             Term::Var(_) => match var_map.get(self.term) {
-                Term::Var(s) => AST::var(s),
+                Term::Var(s) => AST {
+                    annotation: self.annotation,
+                    ..AST::var(s)
+                },
                 _ => panic!("Map of variables must only contain variables"),
             },
-            // Remap an abstraction - remap the param and body
+            // Remap an abstraction - remap the param and body, where the param is bound
             Term::Abstr(param, body) => {
-                AST::abstr(param.remap(var_map.clone()), body.remap(var_map))
+                let bound = bound.clone().with(param.term.clone());
+                AST::abstr(
+                    param.remap(var_map.clone(), &bound),
+                    body.remap(var_map, &bound),
+                )
             }
             // Remap an application - remap the left and right terms
-            Term::Apply(f, arg) => AST::apply(f.remap(var_map.clone()), arg.remap(var_map)),
+            // A let binding stays one
+            Term::Apply(f, arg) => AST {
+                is_let: self.is_let,
+                ..AST::apply(f.remap(var_map.clone(), bound), arg.remap(var_map, bound))
+            },
         }
     }
 }
@@ -34,6 +51,6 @@ impl AlphaVariant for AST {
         // Map them onto all binding variables in the term
         let var_map = VarMap::from_sets(fresh_set, new_ast.binding_vars.clone());
         // Perform the remapping
-        new_ast.remap(var_map)
+        new_ast.remap(var_map, &VarSet::new())
     }
 }
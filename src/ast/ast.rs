@@ -1,12 +1,13 @@
-use crate::alpha::AlhaConversion;
 use crate::ast::Term;
 use crate::lexer::tokenize;
-use crate::parser::{parse, ParsingError};
+use crate::parser::parse;
+use crate::span::Span;
+use crate::types::Type;
 use crate::variables::VarGen;
 use crate::variables::VarSet;
-use regex::Error as RegexError;
 use std::cmp::Ordering;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 
 #[derive(Debug)]
 pub enum EvalError {
@@ -14,24 +15,30 @@ pub enum EvalError {
     ParsingError(String),
 }
 
-impl From<RegexError> for EvalError {
-    fn from(err: RegexError) -> Self {
-        EvalError::TokenizationError(format!("{:?}", err))
-    }
-}
-
-impl From<ParsingError> for EvalError {
-    fn from(err: ParsingError) -> Self {
-        EvalError::ParsingError(format!("{:?}", err))
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::TokenizationError(message) | EvalError::ParsingError(message) => {
+                write!(f, "{}", message)
+            }
+        }
     }
 }
 
-#[derive(Hash, Clone)]
+#[derive(Clone)]
 pub struct AST {
     pub term: Term,
     pub free_vars: VarSet,
     pub binding_vars: VarSet,
     pub is_reducible: bool,
+    /// Number of nested abstractions and applications, the recursions over the term go as deep
+    pub depth: usize,
+    /// Position in the source the term was parsed from, if any
+    pub span: Option<Span>,
+    /// Type of a parameter of an abstraction, if it is annotated
+    pub annotation: Option<Type>,
+    /// Whether the application was written as let x = a in b, which is parsed as ($x -> b) a
+    pub is_let: bool,
 }
 
 impl AST {
@@ -44,32 +51,89 @@ impl AST {
             free_vars,
             binding_vars,
             is_reducible: false,
+            depth: 0,
+            span: None,
+            annotation: None,
+            is_let: false,
         }
     }
     pub fn abstr(param: AST, body: AST) -> AST {
         let free_vars = body.free_vars.clone() - param.term.clone();
         let binding_vars = VarSet::from(param.term.clone()) | body.binding_vars.clone();
-        let term = Term::Abstr(Box::new(param), Box::new(body.clone()));
+        let is_reducible = body.is_reducible;
+        let depth = body.depth + 1;
+        // The parts are moved into the node, copying them would make every rebuilt level
+        // cost as much as the whole subterm
+        let term = Term::Abstr(Box::new(param), Box::new(body));
         AST {
             term,
             free_vars,
             binding_vars,
-            is_reducible: body.is_reducible,
+            is_reducible,
+            depth,
+            span: None,
+            annotation: None,
+            is_let: false,
         }
     }
     pub fn apply(f: AST, arg: AST) -> AST {
         let free_vars = f.free_vars.clone() | arg.free_vars.clone();
         let binding_vars = f.binding_vars.clone() | arg.binding_vars.clone();
-        let term = Term::Apply(Box::new(f.clone()), Box::new(arg.clone()));
         let is_reducible = match f.term {
             Term::Abstr(_, _) => true,
             _ => f.is_reducible || arg.is_reducible,
         };
+        let depth = f.depth.max(arg.depth) + 1;
+        let term = Term::Apply(Box::new(f), Box::new(arg));
         AST {
             term,
             free_vars,
             binding_vars,
             is_reducible,
+            depth,
+            span: None,
+            annotation: None,
+            is_let: false,
+        }
+    }
+    /// Return the term with its position in the source
+    pub fn with_span(self, span: Span) -> AST {
+        AST {
+            span: Some(span),
+            ..self
+        }
+    }
+    /// Return the term with a type annotation
+    pub fn with_annotation(self, ty: Type) -> AST {
+        AST {
+            annotation: Some(ty),
+            ..self
+        }
+    }
+    /// Return the application marked as a let binding
+    pub fn into_let(self) -> AST {
+        AST {
+            is_let: true,
+            ..self
+        }
+    }
+    /// Return the term without the positions in the source, e.g. to be used in another input
+    pub fn without_spans(self) -> AST {
+        match self.term {
+            Term::Var(_) => AST { span: None, ..self },
+            Term::Abstr(param, body) => AST::abstr(param.without_spans(), body.without_spans()),
+            Term::Apply(f, arg) => AST {
+                is_let: self.is_let,
+                ..AST::apply(f.without_spans(), arg.without_spans())
+            },
+        }
+    }
+    /// Number of nodes of the term, i.e. variables, abstractions and applications
+    pub fn size(&self) -> usize {
+        match &self.term {
+            Term::Var(_) => 1,
+            Term::Abstr(_, body) => 1 + body.size(),
+            Term::Apply(f, arg) => 1 + f.size() + arg.size(),
         }
     }
     pub fn all_vars(&self) -> VarSet {
@@ -85,41 +149,37 @@ impl AST {
         let new_var = candidates
             .find(|name| !varset.contains(&Term::Var(name.clone())))
             .expect("Ran out of variable names");
-        println!("Generated new name: {:?}", new_var);
         AST::var(new_var)
     }
 
     pub fn eval(input: &str) -> Result<AST, EvalError> {
-        let tokens = tokenize(input)?;
-        let ast = parse(&tokens)?;
+        let tokens =
+            tokenize(input).map_err(|err| EvalError::TokenizationError(err.render(input)))?;
+        let ast = parse(&tokens).map_err(|err| EvalError::ParsingError(err.render(input)))?;
         Ok(ast)
     }
 }
 
 impl PartialEq for AST {
+    /// Terms are equal if they are alpha-equivalent, i.e. have the same nameless representation
     fn eq(&self, other: &Self) -> bool {
-        match self.free_vars == other.free_vars {
-            false => false,
-            true => {
-                let right = &other.alpha_convert(self.clone());
-                let left = self.alpha_convert(other.clone());
-                match (left, right) {
-                    (Err(_), _) | (_, Err(_)) => false,
-                    _ => true,
-                }
-            }
-        }
+        self.to_de_bruijn() == other.to_de_bruijn()
     }
 }
 
 impl Eq for AST {}
 
+impl Hash for AST {
+    /// Hash the nameless representation, so alpha-equivalent terms hash the same
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_de_bruijn().hash(state);
+    }
+}
+
 impl Ord for AST {
+    /// Order by the nameless representation, which agrees with alpha-equivalence
     fn cmp(&self, other: &AST) -> Ordering {
-        match self == other {
-            true => Ordering::Equal,
-            _ => format!("{:?}", self).cmp(&format!("{:?}", other)),
-        }
+        self.to_de_bruijn().cmp(&other.to_de_bruijn())
     }
 }
 
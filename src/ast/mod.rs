@@ -1,7 +1,5 @@
 pub mod ast;
 pub mod term;
-pub mod termmap;
 
 pub use self::ast::AST;
 pub use self::term::Term;
-pub use self::termmap::TermMap;
@@ -0,0 +1,68 @@
+use crate::ast::AST;
+use crate::beta::{BetaReduction, EvalOrder, ReductionLimits, ReductionResult};
+use crate::cek::Cek;
+use crate::krivine::Krivine;
+use crate::nbe;
+use crate::vm::{Program, Vm};
+use std::fmt;
+use std::str::FromStr;
+
+/// Type represents the evaluator of the terms
+///  - *Tree*: rewriting of the syntax tree to the normal form in the chosen evaluation order
+///  - *Krivine*: the Krivine machine, call-by-name to the weak head normal form
+///  - *Cek*: the CEK machine, call-by-value to a value, without reducing under abstractions
+///  - *Nbe*: normalization by evaluation to the normal form
+///  - *Vm*: the term compiled to bytecode and run on the virtual machine, as *Krivine*
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    #[default]
+    Tree,
+    Krivine,
+    Cek,
+    Nbe,
+    Vm,
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tree" => Ok(Backend::Tree),
+            "krivine" => Ok(Backend::Krivine),
+            "cek" => Ok(Backend::Cek),
+            "nbe" => Ok(Backend::Nbe),
+            "vm" => Ok(Backend::Vm),
+            _ => Err(format!(
+                "Unknown backend '{}', expected tree, krivine, cek, nbe or vm",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Backend::Tree => write!(f, "tree"),
+            Backend::Krivine => write!(f, "krivine"),
+            Backend::Cek => write!(f, "cek"),
+            Backend::Nbe => write!(f, "nbe"),
+            Backend::Vm => write!(f, "vm"),
+        }
+    }
+}
+
+impl Backend {
+    /// Reduce the term within the limits. Only the tree backend uses the evaluation order,
+    /// the machines have a strategy of their own
+    pub fn reduce(&self, ast: AST, order: &EvalOrder, limits: &ReductionLimits) -> ReductionResult {
+        match self {
+            Backend::Tree => ast.reduce_bounded(Some(order), limits),
+            Backend::Krivine => Krivine::run(&ast, limits),
+            Backend::Cek => Cek::run(&ast, limits),
+            Backend::Nbe => nbe::normalize(&ast, limits),
+            Backend::Vm => Vm::new(&Program::compile(&ast)).run(limits),
+        }
+    }
+}
@@ -0,0 +1,4 @@
+//! This module selects the evaluator that reduces the terms
+pub mod backend;
+
+pub use self::backend::Backend;
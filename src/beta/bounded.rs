@@ -0,0 +1,146 @@
+use crate::ast::AST;
+use crate::beta::{EvalOrder, ReductionTrace};
+use crate::printer::Printer;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// The deepest term a bounded reduction works on. Every step recurses over the term, so a
+/// deeper one could overflow the stack. One step at most doubles the depth
+pub const MAX_DEPTH: usize = 1 << 12;
+
+/// Type represents the limits of a bounded reduction
+///  - *fuel*: the maximum number of reduction steps
+///  - *deadline*: the point in time after which the reduction stops
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReductionLimits {
+    pub fuel: Option<usize>,
+    pub deadline: Option<Instant>,
+}
+
+impl ReductionLimits {
+    /// Create limits that never stop the reduction
+    pub fn new() -> Self {
+        ReductionLimits {
+            fuel: None,
+            deadline: None,
+        }
+    }
+
+    /// Return a new copy limited to the given point in time
+    pub fn with_deadline(self, deadline: Instant) -> Self {
+        ReductionLimits {
+            deadline: Some(deadline),
+            ..self
+        }
+    }
+
+    /// Return a new copy limited to the given duration from now
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+}
+
+/// Type represents the outcome of a bounded reduction
+///  - *Normalized*: the term reached its normal form, or the form the reduction aims for
+///  - *WeakHead*: the term reached a weak head normal form, but has redexes left under
+///    abstractions or in arguments, which the machines do not reduce
+///  - *OutOfFuel*: the step budget ran out, carries the last term and the number of steps
+///  - *TimedOut*: the deadline passed, carries the last term
+///  - *Aborted*: the reduction could not go on, carries the last term and the reason
+#[derive(Debug, Clone)]
+pub enum ReductionResult {
+    Normalized(AST),
+    WeakHead(AST),
+    OutOfFuel(AST, usize),
+    TimedOut(AST),
+    Aborted(AST, String),
+}
+
+impl ReductionResult {
+    /// The result of a machine that stops at the weak head. The term is only normalized
+    /// if it has no redex left
+    pub fn weak_head(ast: AST) -> Self {
+        match ast.is_reducible {
+            true => ReductionResult::WeakHead(ast),
+            false => ReductionResult::Normalized(ast),
+        }
+    }
+
+    /// Show the result with its term printed by the printer
+    pub fn show(&self, printer: &Printer) -> String {
+        match self {
+            ReductionResult::Normalized(ast) => printer.print(ast),
+            ReductionResult::WeakHead(ast) => {
+                format!("Weak head normal form: {}", printer.print(ast))
+            }
+            ReductionResult::OutOfFuel(ast, steps) => {
+                format!("Stopped after {} steps: {}", steps, printer.print(ast))
+            }
+            ReductionResult::TimedOut(ast) => format!("Timed out: {}", printer.print(ast)),
+            ReductionResult::Aborted(ast, reason) => format!("{}: {}", reason, printer.print(ast)),
+        }
+    }
+}
+
+impl fmt::Display for ReductionResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.show(&Printer::new()))
+    }
+}
+
+/// The reason a reduction stopped at a term deeper than the limit
+fn too_deep() -> String {
+    format!("Stopped at a term nested deeper than {} levels", MAX_DEPTH)
+}
+
+/// Drive the reduction one step at a time until the term is normalized or a limit is hit
+pub fn reduce_bounded(ast: AST, order: EvalOrder, limits: &ReductionLimits) -> ReductionResult {
+    let mut trace = ReductionTrace::new(ast, order);
+    loop {
+        if limits.fuel.is_some_and(|fuel| trace.steps() >= fuel) {
+            return ReductionResult::OutOfFuel(trace.term().clone(), trace.steps());
+        }
+        if limits
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return ReductionResult::TimedOut(trace.term().clone());
+        }
+        if trace.term().depth > MAX_DEPTH {
+            return ReductionResult::Aborted(trace.term().clone(), too_deep());
+        }
+        if trace.next().is_none() {
+            return ReductionResult::Normalized(trace.term().clone());
+        }
+    }
+}
+
+/// Apply the reduction step until it finds nothing to reduce or a limit is hit
+pub(super) fn drive(
+    ast: AST,
+    limits: &ReductionLimits,
+    step: impl Fn(AST) -> Option<AST>,
+) -> ReductionResult {
+    let (mut ast, mut steps) = (ast, 0);
+    loop {
+        if limits.fuel.is_some_and(|fuel| steps >= fuel) {
+            return ReductionResult::OutOfFuel(ast, steps);
+        }
+        if limits
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return ReductionResult::TimedOut(ast);
+        }
+        if ast.depth > MAX_DEPTH {
+            return ReductionResult::Aborted(ast, too_deep());
+        }
+        match step(ast.clone()) {
+            Some(next) => {
+                ast = next;
+                steps += 1;
+            }
+            None => return ReductionResult::Normalized(ast),
+        }
+    }
+}
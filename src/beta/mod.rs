@@ -1,5 +1,9 @@
 //! This module handles the beta reduction of terms
+pub mod bounded;
 pub mod reduction;
+pub mod trace;
 
+pub use self::bounded::{ReductionLimits, ReductionResult};
 pub use self::reduction::BetaReduction;
 pub use self::reduction::EvalOrder;
+pub use self::trace::ReductionTrace;
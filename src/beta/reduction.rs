@@ -1,16 +1,59 @@
 use crate::alpha::AlphaVariant;
 use crate::ast::Term;
-use crate::ast::TermMap;
 use crate::ast::AST;
+use crate::beta::bounded::{drive, reduce_bounded};
+use crate::beta::{ReductionLimits, ReductionResult, ReductionTrace};
+use crate::need::CallByNeed;
 use crate::substitution::Substitution;
+use std::fmt;
+use std::str::FromStr;
 
+/// Type represents the order in which the redexes of a term are contracted
+///  - *Normal*: the leftmost outermost redex first
+///  - *Applicative*: the arguments are normalized before the redex is contracted
+///  - *Lazy*: call-by-need, every argument is a shared thunk evaluated at most once. It has
+///    no single steps on the tree, so a trace of it takes the steps of *Normal*
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum EvalOrder {
+    #[default]
     Normal,
     Applicative,
     Lazy,
 }
+
+impl FromStr for EvalOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "normal" => Ok(EvalOrder::Normal),
+            "applicative" => Ok(EvalOrder::Applicative),
+            "lazy" => Ok(EvalOrder::Lazy),
+            _ => Err(format!(
+                "Unknown evaluation order '{}', expected normal, applicative or lazy",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for EvalOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalOrder::Normal => write!(f, "normal"),
+            EvalOrder::Applicative => write!(f, "applicative"),
+            EvalOrder::Lazy => write!(f, "lazy"),
+        }
+    }
+}
+
 pub trait BetaReduction {
     fn reduce(self, order: Option<&EvalOrder>) -> AST;
+    fn trace(self, order: Option<&EvalOrder>) -> ReductionTrace;
+    fn reduce_bounded(self, order: Option<&EvalOrder>, limits: &ReductionLimits)
+        -> ReductionResult;
+    fn reduce_to_whnf(self, limits: &ReductionLimits) -> ReductionResult;
+    fn reduce_to_hnf(self, limits: &ReductionLimits) -> ReductionResult;
 }
 
 impl AST {
@@ -19,22 +62,21 @@ impl AST {
         match self.is_reducible {
             // If the term cannot be reduced further, just return it
             false => self,
-            // Otherwise look up the current term in the memo
-            true => match self.clone().term {
-                // A variable reduces to itself. It could have come from the memo.
+            // Otherwise take the term apart, the subterms are moved rather than copied
+            true => match self.term {
+                // A variable reduces to itself
                 Term::Var(s) => AST::var(s),
                 // For abstraction, just recusively reduce the body
                 Term::Abstr(param, body) => match (*body).beta_step(order) {
                     ast => AST::abstr(*param, ast),
                 },
                 // For application, it depends if it is a redex
-                Term::Apply(f, arg) => match (*f).term {
+                Term::Apply(f, arg) => match f.term {
                     // If it is a redex, perform the substitution of param in the body with the argument
-                    Term::Abstr(param, body) => self.reduce_redex(order, *param, *body, *arg),
+                    Term::Abstr(param, body) => AST::reduce_redex(order, *param, *body, *arg),
                     // If it is not a redex, just recursively reduce the left and right side
-                    // The first reduction may enhance the memo so we need to call it one at a time
-                    _ => {
-                        let f_reduced = f.beta_step(order);
+                    term => {
+                        let f_reduced = AST { term, ..*f }.beta_step(order);
                         let arg_reduced = arg.beta_step(order);
                         AST::apply(f_reduced, arg_reduced)
                     }
@@ -42,40 +84,11 @@ impl AST {
             },
         }
     }
-    /// One step of a beta reduction. It is recursive so it may actually perform multiple substitutions
-    fn lazy_beta_step(self, memo: TermMap) -> (AST, TermMap) {
-        match self.is_reducible {
-            // If the term cannot be reduced further, just return it
-            false => (self, memo),
-            // Otherwise look up the current term in the memo
-            true => match memo.get(self.clone()).term {
-                // A variable reduces to itself. It could have come from the memo.
-                Term::Var(s) => (AST::var(s), memo),
-                // For abstraction, just recusively reduce the body
-                Term::Abstr(param, body) => match (*body).lazy_beta_step(memo) {
-                    (ast, new_memo) => (AST::abstr(*param, ast), new_memo),
-                },
-                // For application, it depends if it is a redex
-                Term::Apply(f, arg) => match (*f).term {
-                    // If it is a redex, perform the substitution of param in the body with the argument
-                    Term::Abstr(param, body) => self.lazy_reduce_redex(memo, *param, *body, *arg),
-                    // If it is not a redex, just recursively reduce the left and right side
-                    // The first reduction may enhance the memo so we need to call it one at a time
-                    _ => match f.lazy_beta_step(memo) {
-                        (f_ast, new_memo) => match arg.lazy_beta_step(new_memo) {
-                            (arg_ast, final_memo) => (AST::apply(f_ast, arg_ast), final_memo),
-                        },
-                    },
-                },
-            },
-        }
-    }
-
     /// Reduction of a redex term, i.e. the application of an abstraction to another term
-    fn reduce_redex(self, order: &EvalOrder, param: AST, body: AST, arg: AST) -> AST {
+    fn reduce_redex(order: &EvalOrder, param: AST, body: AST, arg: AST) -> AST {
         // Try to perform the substitution as-is.
         match order {
-            EvalOrder::Normal => body
+            EvalOrder::Normal | EvalOrder::Lazy => body
                 .clone()
                 .substitute(param.term.clone(), arg.clone())
                 // We expect that we may need to first create an alpha variant of the body if some of the
@@ -91,21 +104,76 @@ impl AST {
                         .substitute(param.term, arg.reduce(Some(order)))
                         .unwrap()
                 }),
-            EvalOrder::Lazy => panic!("This function should never be called with lazy evaluation"),
         }
     }
 
-    /// Reduction of a redex term, i.e. the application of an abstraction to another term
-    fn lazy_reduce_redex(self, memo: TermMap, param: AST, body: AST, arg: AST) -> (AST, TermMap) {
-        // Try to perform the substitution as-is.
-        let substituted_body = body
-            .clone()
-            .substitute(param.term.clone(), arg.clone())
-            // We expect that we may need to first create an alpha variant of the body if some of the
-            // free variables of arg appear as bindning in body
-            .unwrap_or_else(|_| body.alpha_variant().substitute(param.term, arg).unwrap());
-        // Add the pair pair of evaluation to the memo for later lazy evaluation
-        (substituted_body.clone(), memo.with(self, substituted_body))
+    /// A single step of a reduction that contracts exactly one redex, picked according to the order.
+    /// Returns the new term together with the contracted redex, or None if the term is in normal form.
+    /// The subterms are moved down and the path to the redex is rebuilt, only the redex is copied
+    pub(super) fn single_step(self, order: &EvalOrder) -> Option<(AST, AST)> {
+        match self.is_reducible {
+            // A term in normal form has no redex to contract
+            false => None,
+            true => match self.term {
+                // A variable is never reducible
+                Term::Var(_) => None,
+                // For abstraction, contract a redex in the body
+                Term::Abstr(param, body) => (*body)
+                    .single_step(order)
+                    .map(|(ast, redex)| (AST::abstr(*param, ast), redex)),
+                // For application, it depends on the order and whether it is a redex
+                Term::Apply(f, arg) => match (&f.term, order) {
+                    // Applicative order first brings the argument to its normal form
+                    (Term::Abstr(_, _), EvalOrder::Applicative) if arg.is_reducible => (*arg)
+                        .single_step(order)
+                        .map(|(ast, redex)| (AST::apply(*f, ast), redex)),
+                    // Otherwise the redex itself is contracted
+                    (Term::Abstr(_, _), _) => {
+                        let redex = AST {
+                            term: Term::Apply(f.clone(), arg.clone()),
+                            ..self
+                        };
+                        let (param, body) = match f.term {
+                            Term::Abstr(param, body) => (*param, *body),
+                            _ => unreachable!("The function of a redex is an abstraction"),
+                        };
+                        Some((AST::reduce_redex(order, param, body, *arg), redex))
+                    }
+                    // If it is not a redex, contract the leftmost redex on the left side and then on the right side
+                    _ => match f.is_reducible {
+                        true => (*f)
+                            .single_step(order)
+                            .map(|(ast, redex)| (AST::apply(ast, *arg), redex)),
+                        false => (*arg)
+                            .single_step(order)
+                            .map(|(ast, redex)| (AST::apply(*f, ast), redex)),
+                    },
+                },
+            },
+        }
+    }
+
+    /// One step of head reduction, contracting the redex in the head position of the term.
+    /// Abstractions are only entered when reducing to the head normal form, so the weak head
+    /// normal form treats every abstraction as a value
+    pub(super) fn head_step(self, under_abstractions: bool) -> Option<AST> {
+        match self.term {
+            Term::Var(_) => None,
+            Term::Abstr(param, body) => match under_abstractions {
+                true => body
+                    .head_step(under_abstractions)
+                    .map(|body| AST::abstr(*param, body)),
+                false => None,
+            },
+            Term::Apply(f, arg) => match f.term {
+                Term::Abstr(param, body) => {
+                    Some(AST::reduce_redex(&EvalOrder::Normal, *param, *body, *arg))
+                }
+                term => AST { term, ..*f }
+                    .head_step(under_abstractions)
+                    .map(|f| AST::apply(f, *arg)),
+            },
+        }
     }
 
     /// The main reduction loop that may also never terminate
@@ -119,32 +187,156 @@ impl AST {
             false => new_ast.clone().beta_reduce(order),
         }
     }
-
-    /// The main reduction loop that may also never terminate
-    fn lazy_beta_reduce(self, memo: Option<TermMap>) -> (AST, TermMap) {
-        let memo = memo.unwrap_or(TermMap::new());
-        // Perform one beta step on the term
-        let (new_ast, new_memo) = self.clone().lazy_beta_step(memo);
-        // If the term is not reducible or has not yet been processed before, keep processiong
-        match !new_ast.is_reducible || new_memo.contains(&new_ast) {
-            true => (new_ast, new_memo),
-            // Before each new process, add the result of the previous call to the memo
-            false => new_ast
-                .clone()
-                .lazy_beta_reduce(Some(new_memo.with(self, new_ast))),
-        }
-    }
 }
 
 impl BetaReduction for AST {
-    /// A beta reduction loop that keeps the memo hidden
+    /// The beta reduction loop to the normal form
     fn reduce(self, order: Option<&EvalOrder>) -> AST {
         let order = order.unwrap_or(&EvalOrder::Normal);
         match order {
-            EvalOrder::Lazy => match self.lazy_beta_reduce(None) {
-                (result, memo) => result,
-            },
+            // The arguments are shared thunks, which the tree has no place for
+            EvalOrder::Lazy => CallByNeed::new()
+                .normalize(&self)
+                .expect("An evaluation without limits only stops at the normal form"),
             EvalOrder::Normal | EvalOrder::Applicative => self.beta_reduce(order),
         }
     }
+
+    /// An iterator over every intermediate term of the reduction
+    fn trace(self, order: Option<&EvalOrder>) -> ReductionTrace {
+        ReductionTrace::new(self, *order.unwrap_or(&EvalOrder::Normal))
+    }
+
+    /// A reduction that stops when it runs out of steps or time, so it is safe for untrusted terms
+    fn reduce_bounded(
+        self,
+        order: Option<&EvalOrder>,
+        limits: &ReductionLimits,
+    ) -> ReductionResult {
+        match order.unwrap_or(&EvalOrder::Normal) {
+            EvalOrder::Lazy => CallByNeed::run(&self, limits),
+            order => reduce_bounded(self, *order, limits),
+        }
+    }
+
+    /// Reduce the head until the term is an abstraction or a variable applied to arguments
+    fn reduce_to_whnf(self, limits: &ReductionLimits) -> ReductionResult {
+        drive(self, limits, |ast| ast.head_step(false))
+    }
+
+    /// Reduce the head, also under abstractions, until the term has the shape
+    /// $x1, ..., xn -> y t1 ... tk
+    fn reduce_to_hnf(self, limits: &ReductionLimits) -> ReductionResult {
+        drive(self, limits, |ast| ast.head_step(true))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BetaReduction;
+    use crate::ast::AST;
+    use crate::beta::bounded::MAX_DEPTH;
+    use crate::beta::{EvalOrder, ReductionLimits, ReductionResult};
+    use crate::with_large_stack;
+    use std::time::{Duration, Instant};
+
+    fn parse(input: &str) -> AST {
+        AST::eval(input).unwrap()
+    }
+
+    #[test]
+    fn divergent_terms_stop_in_bounded_time() {
+        with_large_stack(|| {
+            let limits = ReductionLimits {
+                fuel: Some(10_000),
+                deadline: None,
+            };
+            let start = Instant::now();
+            // The term stays the same size
+            let omega = parse("($x -> x x) ($x -> x x)");
+            for order in [EvalOrder::Normal, EvalOrder::Applicative] {
+                match omega.clone().reduce_bounded(Some(&order), &limits) {
+                    ReductionResult::OutOfFuel(_, steps) => assert_eq!(steps, 10_000),
+                    result => panic!("Expected to run out of fuel, got {}", result),
+                }
+            }
+            // The term grows with every step, it used to be copied whole at every level
+            let y = parse("($x -> f (x x)) ($x -> f (x x))");
+            let limits = ReductionLimits {
+                fuel: Some(300),
+                ..limits
+            };
+            match y.reduce_bounded(None, &limits) {
+                ReductionResult::OutOfFuel(ast, steps) => {
+                    assert_eq!(steps, 300);
+                    assert_eq!(ast.depth, 304);
+                }
+                result => panic!("Expected to run out of fuel, got {}", result),
+            }
+            assert!(start.elapsed() < Duration::from_secs(60));
+        });
+    }
+
+    #[test]
+    fn lazy_order_shares_arguments() {
+        // The argument is used twice, but only evaluated once
+        let ast = parse("($x -> x x) (($y -> y) a)");
+        let limits = ReductionLimits {
+            fuel: Some(2),
+            deadline: None,
+        };
+        match ast.clone().reduce_bounded(Some(&EvalOrder::Lazy), &limits) {
+            ReductionResult::Normalized(ast) => assert_eq!(ast, parse("a a")),
+            result => panic!("Expected a normal form, got {}", result),
+        }
+        assert!(matches!(
+            ast.clone()
+                .reduce_bounded(Some(&EvalOrder::Normal), &limits),
+            ReductionResult::OutOfFuel(_, 2)
+        ));
+        assert_eq!(ast.reduce(Some(&EvalOrder::Lazy)), parse("a a"));
+    }
+
+    #[test]
+    fn deep_terms_abort() {
+        with_large_stack(|| {
+            // A redex around f applied to x more times than the limit
+            let deep = (0..MAX_DEPTH).fold(parse("x"), |ast, _| AST::apply(parse("f"), ast));
+            let redex = AST::apply(parse("$x -> x"), deep);
+            match redex.clone().reduce_bounded(None, &ReductionLimits::new()) {
+                ReductionResult::Aborted(ast, _) => assert_eq!(ast, redex),
+                result => panic!("Expected to abort, got {}", result),
+            }
+            match redex.clone().reduce_to_whnf(&ReductionLimits::new()) {
+                ReductionResult::Aborted(ast, _) => assert_eq!(ast, redex),
+                result => panic!("Expected to abort, got {}", result),
+            }
+        });
+    }
+
+    #[test]
+    fn weak_head_stops_at_abstraction() {
+        // The redex is under the abstraction, so the term already is in weak head normal form
+        let ast = parse("$x -> ($y -> y) x");
+        match ast.clone().reduce_to_whnf(&ReductionLimits::new()) {
+            ReductionResult::Normalized(result) => assert_eq!(result, ast),
+            result => panic!("Expected a weak head normal form, got {}", result),
+        }
+        match parse("($x -> $y -> x) (($z -> z) a)").reduce_to_whnf(&ReductionLimits::new()) {
+            ReductionResult::Normalized(result) => assert_eq!(result, parse("$y -> ($z -> z) a")),
+            result => panic!("Expected a weak head normal form, got {}", result),
+        }
+    }
+
+    #[test]
+    fn head_reduces_under_abstraction() {
+        // The head redex under the abstraction is reduced, the one in the argument is kept
+        let ast = parse("$x -> ($y -> y) x (($z -> z) a)");
+        match ast.reduce_to_hnf(&ReductionLimits::new()) {
+            ReductionResult::Normalized(result) => {
+                assert_eq!(result, parse("$x -> x (($z -> z) a)"))
+            }
+            result => panic!("Expected a head normal form, got {}", result),
+        }
+    }
 }
@@ -0,0 +1,87 @@
+use crate::ast::AST;
+use crate::beta::EvalOrder;
+
+/// Type represents a single step of a reduction
+///  - *term*: the whole term after the step
+///  - *redex*: the redex that was contracted in the step
+///  - *order*: the evaluation order that picked the redex
+#[derive(Debug, Clone)]
+pub struct ReductionStep {
+    pub term: AST,
+    pub redex: AST,
+    pub order: EvalOrder,
+}
+
+/// Iterator over the intermediate terms of a reduction, contracting one redex per step.
+/// It ends once the term reaches its normal form, so it may also never end
+pub struct ReductionTrace {
+    current: AST,
+    order: EvalOrder,
+    steps: usize,
+}
+
+impl ReductionTrace {
+    pub fn new(ast: AST, order: EvalOrder) -> Self {
+        ReductionTrace {
+            current: ast,
+            order,
+            steps: 0,
+        }
+    }
+
+    /// The term reached by the last step
+    pub fn term(&self) -> &AST {
+        &self.current
+    }
+
+    /// Number of steps performed so far
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+}
+
+impl Iterator for ReductionTrace {
+    type Item = ReductionStep;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.current.clone().single_step(&self.order) {
+            None => None,
+            Some((term, redex)) => {
+                self.current = term.clone();
+                self.steps += 1;
+                Some(ReductionStep {
+                    term,
+                    redex,
+                    order: self.order,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::AST;
+    use crate::beta::{BetaReduction, EvalOrder};
+
+    fn parse(input: &str) -> AST {
+        AST::eval(input).unwrap()
+    }
+
+    #[test]
+    fn steps_record_order() {
+        // The orders pick different redexes first, but reach the same normal form
+        let ast = parse("($x, y -> y x) (($z -> z) a) ($w -> w)");
+        let expected = [
+            (EvalOrder::Normal, "($y -> y (($z -> z) a)) $w -> w"),
+            (EvalOrder::Applicative, "($x, y -> y x) a ($w -> w)"),
+            (EvalOrder::Lazy, "($y -> y (($z -> z) a)) $w -> w"),
+        ];
+        for (order, first) in expected {
+            let steps: Vec<_> = ast.clone().trace(Some(&order)).collect();
+            assert!(steps.iter().all(|step| step.order == order), "{}", order);
+            assert_eq!(steps[0].term, parse(first), "{}", order);
+            assert_eq!(steps.last().unwrap().term, parse("a"), "{}", order);
+        }
+    }
+}
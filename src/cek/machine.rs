@@ -0,0 +1,236 @@
+use crate::ast::AST;
+use crate::beta::{ReductionLimits, ReductionResult};
+use crate::debruijn::DeBruijn;
+use crate::variables::VarGen;
+use std::rc::Rc;
+use std::time::Instant;
+
+/// Type represents a value, i.e. a term evaluated by call-by-value
+///  - *Closure*: the body of an abstraction together with the environment it was created in
+///  - *Neutral*: a free variable applied to values, which cannot be reduced
+#[derive(Clone)]
+pub enum Value {
+    Closure(Rc<DeBruijn>, Env),
+    Neutral(String, Vec<Value>),
+}
+
+/// Type represents an environment, i.e. the values bound to de Bruijn indices.
+/// The innermost binding is the head of the list, so index 0 is the first node
+#[derive(Clone, Default)]
+pub struct Env {
+    head: Option<Rc<EnvNode>>,
+}
+
+struct EnvNode {
+    value: Value,
+    tail: Env,
+}
+
+impl Env {
+    pub fn new() -> Self {
+        Env { head: None }
+    }
+
+    /// Return a new environment with the value bound to index 0
+    pub fn with(&self, value: Value) -> Self {
+        Env {
+            head: Some(Rc::new(EnvNode {
+                value,
+                tail: self.clone(),
+            })),
+        }
+    }
+
+    /// Look up the value bound to the index
+    pub fn get(&self, index: usize) -> &Value {
+        let mut env = self;
+        for _ in 0..index {
+            env = &env.head.as_ref().expect("Unbound de Bruijn index").tail;
+        }
+        &env.head.as_ref().expect("Unbound de Bruijn index").value
+    }
+}
+
+impl Value {
+    /// Read the value back into a term
+    pub fn unload(&self) -> DeBruijn {
+        match self {
+            Value::Closure(body, env) => DeBruijn::Abstr(Rc::new(unload_in(body, env, 1))),
+            Value::Neutral(name, args) => {
+                args.iter().fold(DeBruijn::Free(name.clone()), |term, arg| {
+                    DeBruijn::Apply(Rc::new(term), Rc::new(arg.unload()))
+                })
+            }
+        }
+    }
+}
+
+/// Substitute the values of the environment into a term under the given number of its own binders
+fn unload_in(term: &DeBruijn, env: &Env, depth: usize) -> DeBruijn {
+    match term {
+        DeBruijn::Bound(index) if *index < depth => DeBruijn::Bound(*index),
+        DeBruijn::Bound(index) => env.get(index - depth).unload().shift(depth as isize, 0),
+        DeBruijn::Free(s) => DeBruijn::Free(s.clone()),
+        DeBruijn::Abstr(body) => DeBruijn::Abstr(Rc::new(unload_in(body, env, depth + 1))),
+        DeBruijn::Apply(f, arg) => DeBruijn::Apply(
+            Rc::new(unload_in(f, env, depth)),
+            Rc::new(unload_in(arg, env, depth)),
+        ),
+    }
+}
+
+/// Type represents a frame of the continuation, i.e. what remains to be done with a value
+///  - *Arg*: the value is a function, its argument is evaluated next in the environment
+///  - *Call*: the value is an argument, the evaluated function is applied to it next
+#[derive(Clone)]
+pub enum Frame {
+    Arg(Rc<DeBruijn>, Env),
+    Call(Value),
+}
+
+/// Type represents what the machine does next
+///  - *Eval*: evaluate a term in an environment
+///  - *Return*: pass a value to the continuation
+#[derive(Clone)]
+enum Control {
+    Eval(Rc<DeBruijn>, Env),
+    Return(Value),
+}
+
+/// Type represents the state of a CEK machine
+///  - *control*: the term being evaluated in its environment, or the value just computed
+///  - *continuation*: the frames waiting for the value, the innermost one on top
+///  - *steps*: the number of beta reductions performed so far
+pub struct Cek {
+    control: Control,
+    continuation: Vec<Frame>,
+    steps: usize,
+}
+
+impl Cek {
+    /// Load a term into the machine
+    pub fn new(ast: &AST) -> Self {
+        Cek {
+            control: Control::Eval(Rc::new(ast.to_de_bruijn()), Env::new()),
+            continuation: Vec::new(),
+            steps: 0,
+        }
+    }
+
+    /// Run the machine up to the next beta reduction. Returns false when the machine stops,
+    /// i.e. a value is returned to an empty continuation
+    pub fn step(&mut self) -> bool {
+        loop {
+            match self.control.clone() {
+                Control::Eval(term, env) => {
+                    self.control = match &*term {
+                        DeBruijn::Bound(index) => Control::Return(env.get(*index).clone()),
+                        DeBruijn::Free(s) => Control::Return(Value::Neutral(s.clone(), Vec::new())),
+                        DeBruijn::Abstr(body) => Control::Return(Value::Closure(body.clone(), env)),
+                        // The function is evaluated first, the argument waits in the continuation
+                        DeBruijn::Apply(f, arg) => {
+                            self.continuation.push(Frame::Arg(arg.clone(), env.clone()));
+                            Control::Eval(f.clone(), env)
+                        }
+                    }
+                }
+                Control::Return(value) => match self.continuation.pop() {
+                    None => return false,
+                    Some(Frame::Arg(arg, env)) => {
+                        self.continuation.push(Frame::Call(value));
+                        self.control = Control::Eval(arg, env);
+                    }
+                    // The argument is a value now, so it is bound and never evaluated again
+                    Some(Frame::Call(Value::Closure(body, env))) => {
+                        self.control = Control::Eval(body, env.with(value));
+                        self.steps += 1;
+                        return true;
+                    }
+                    Some(Frame::Call(Value::Neutral(name, mut args))) => {
+                        args.push(value);
+                        self.control = Control::Return(Value::Neutral(name, args));
+                    }
+                },
+            }
+        }
+    }
+
+    /// Read the current state back into a term, the control plugged into every frame
+    pub fn term(&self) -> AST {
+        let focus = match &self.control {
+            Control::Eval(term, env) => unload_in(term, env, 0),
+            Control::Return(value) => value.unload(),
+        };
+        self.continuation
+            .iter()
+            .rev()
+            .fold(focus, |term, frame| match frame {
+                Frame::Arg(arg, env) => {
+                    DeBruijn::Apply(Rc::new(term), Rc::new(unload_in(arg, env, 0)))
+                }
+                Frame::Call(f) => DeBruijn::Apply(Rc::new(f.unload()), Rc::new(term)),
+            })
+            .to_named(&VarGen::new())
+    }
+
+    /// Evaluate a term to a value or until a limit is hit
+    pub fn run(ast: &AST, limits: &ReductionLimits) -> ReductionResult {
+        let mut machine = Cek::new(ast);
+        loop {
+            if limits.fuel.is_some_and(|fuel| machine.steps >= fuel) {
+                return ReductionResult::OutOfFuel(machine.term(), machine.steps);
+            }
+            if limits
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+            {
+                return ReductionResult::TimedOut(machine.term());
+            }
+            if !machine.step() {
+                return ReductionResult::weak_head(machine.term());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cek;
+    use crate::ast::AST;
+    use crate::beta::{ReductionLimits, ReductionResult};
+
+    fn run(input: &str) -> ReductionResult {
+        let limits = ReductionLimits {
+            fuel: Some(1000),
+            deadline: None,
+        };
+        Cek::run(&AST::eval(input).unwrap(), &limits)
+    }
+
+    #[test]
+    fn value() {
+        match run("($x, y -> y x) a ($z -> z)") {
+            ReductionResult::Normalized(ast) => assert_eq!(ast, AST::eval("a").unwrap()),
+            result => panic!("Expected a normal form, found {}", result),
+        }
+    }
+
+    #[test]
+    fn value_is_not_normalized() {
+        match run("($x -> x) ($y -> ($z -> z) y)") {
+            ReductionResult::WeakHead(ast) => {
+                assert_eq!(ast, AST::eval("$y -> ($z -> z) y").unwrap())
+            }
+            result => panic!("Expected a weak head normal form, found {}", result),
+        }
+    }
+
+    #[test]
+    fn arguments_are_evaluated_first() {
+        // Call-by-value diverges on an argument that is never used
+        match run("($x, y -> y) (($x -> x x) ($x -> x x)) a") {
+            ReductionResult::OutOfFuel(_, steps) => assert_eq!(steps, 1000),
+            result => panic!("Expected to run out of fuel, found {}", result),
+        }
+    }
+}
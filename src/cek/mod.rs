@@ -0,0 +1,5 @@
+//! This module implements the CEK machine, which evaluates terms by call-by-value
+//! with an explicit continuation instead of the Rust stack
+pub mod machine;
+
+pub use self::machine::Cek;
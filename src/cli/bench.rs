@@ -0,0 +1,183 @@
+use crate::ast::AST;
+use crate::backend::Backend;
+use crate::beta::{BetaReduction, EvalOrder, ReductionLimits, ReductionResult};
+use crate::encodings::build::apply;
+use crate::encodings::{church, Church, Encoding, Parigot, Scott};
+use std::time::{Duration, Instant};
+
+/// Programs of the benchmark, with a name to show
+fn programs() -> Vec<(String, AST)> {
+    let mul = [5, 10, 20, 40].map(|n| {
+        let ast = apply(church::mul(), [church::numeral(n), church::numeral(n)]);
+        (format!("mul {} {}", n, n), ast)
+    });
+    let pow = [4, 6, 8, 10].map(|e| {
+        let ast = apply(church::pow(), [church::numeral(2), church::numeral(e)]);
+        (format!("pow 2 {}", e), ast)
+    });
+    let add = [5, 10, 20, 40].map(|n| {
+        let ast = apply(church::add(), [church::numeral(n), church::numeral(n)]);
+        (format!("add {} {}", n, n), ast)
+    });
+    // The sum of the numbers from 1 to n, by folding the list with the sum
+    let sum = [5, 10, 20].map(|n| {
+        let list = church::list((1..=n).map(church::numeral).collect());
+        let ast = apply(church::fold(), [list, church::add(), church::numeral(0)]);
+        (format!("sum [{}]", n), ast)
+    });
+    // The branch that is not taken is the expensive one
+    let branch = [8, 10].map(|e| {
+        let power = apply(church::pow(), [church::numeral(2), church::numeral(e)]);
+        let ast = apply(
+            church::if_then_else(),
+            [church::boolean(false), power, church::numeral(e)],
+        );
+        (format!("if false {}", e), ast)
+    });
+    mul.into_iter()
+        .chain(pow)
+        .chain(add)
+        .chain(sum)
+        .chain(branch)
+        .collect()
+}
+
+/// Normalize the term with the backend and measure how long it took
+fn measure(backend: Backend, ast: &AST, limits: &ReductionLimits) -> (ReductionResult, Duration) {
+    let start = Instant::now();
+    let result = backend.reduce(ast.clone(), &EvalOrder::Normal, limits);
+    (result, start.elapsed())
+}
+
+/// Show a measurement, or why it has none
+fn show(result: &ReductionResult, time: Duration) -> String {
+    match result {
+        ReductionResult::Normalized(_) | ReductionResult::WeakHead(_) => format!("{:.3?}", time),
+        ReductionResult::OutOfFuel(_, steps) => format!("> {} steps", steps),
+        ReductionResult::TimedOut(_) => format!("> {:.0?}", time),
+        ReductionResult::Aborted(_, _) => "aborted".to_string(),
+    }
+}
+
+/// Compare normalization by evaluation against the normal order rewriting of the tree
+/// on Church numeral arithmetic. Every run is limited to the timeout
+pub fn bench(timeout: Duration) {
+    println!("{:<12} {:>14} {:>14}  same", "program", "tree", "nbe");
+    for (name, ast) in programs() {
+        let (tree, tree_time) = measure(
+            Backend::Tree,
+            &ast,
+            &ReductionLimits::new().with_timeout(timeout),
+        );
+        let (nbe, nbe_time) = measure(
+            Backend::Nbe,
+            &ast,
+            &ReductionLimits::new().with_timeout(timeout),
+        );
+        let same = match (&tree, &nbe) {
+            (ReductionResult::Normalized(tree), ReductionResult::Normalized(nbe)) => {
+                (tree == nbe).to_string()
+            }
+            _ => "-".to_string(),
+        };
+        println!(
+            "{:<12} {:>14} {:>14}  {}",
+            name,
+            show(&tree, tree_time),
+            show(&nbe, nbe_time),
+            same
+        );
+    }
+}
+
+/// Operations to compare between the encodings, with a name to show.
+/// The expected result is checked by decoding the normal form. The values are small,
+/// because a Parigot value contains its predecessor twice and grows exponentially
+fn operations(encoding: &dyn Encoding) -> Vec<(String, AST, Expected)> {
+    let mut operations = Vec::new();
+    for n in [2, 5, 10] {
+        let numeral = encoding.numeral(n);
+        operations.push((
+            format!("succ {}", n),
+            AST::apply(encoding.succ(), numeral.clone()),
+            Expected::Numeral(n + 1),
+        ));
+        operations.push((
+            format!("pred {}", n),
+            AST::apply(encoding.pred(), numeral.clone()),
+            Expected::Numeral(n - 1),
+        ));
+        operations.push((
+            format!("iszero {}", n),
+            AST::apply(encoding.is_zero(), numeral),
+            Expected::Bool(false),
+        ));
+    }
+    for n in [3, 6] {
+        // The items count down from n, so the head is n
+        let list = encoding.list((1..=n).rev().map(|i| encoding.numeral(i)).collect());
+        operations.push((
+            format!("cons [{}]", n),
+            apply(encoding.cons(), [encoding.numeral(0), list.clone()]),
+            Expected::Length(n as usize + 1),
+        ));
+        operations.push((
+            format!("head [{}]", n),
+            AST::apply(encoding.head(), list.clone()),
+            Expected::Numeral(n),
+        ));
+        operations.push((
+            format!("tail [{}]", n),
+            AST::apply(encoding.tail(), list),
+            Expected::Length(n as usize - 1),
+        ));
+    }
+    operations
+}
+
+/// Type represents the result an operation should have
+enum Expected {
+    Numeral(u64),
+    Bool(bool),
+    Length(usize),
+}
+
+impl Expected {
+    fn matches(&self, encoding: &dyn Encoding, ast: &AST) -> bool {
+        match self {
+            Expected::Numeral(n) => encoding.as_numeral(ast) == Some(*n),
+            Expected::Bool(b) => ast.as_church_bool() == Some(*b),
+            Expected::Length(n) => encoding.as_list(ast).is_some_and(|items| items.len() == *n),
+        }
+    }
+}
+
+/// Compare the number of reduction steps of the same operations in the Church,
+/// Scott and Parigot encodings. Every reduction is limited to the fuel
+pub fn compare_encodings(order: EvalOrder, fuel: usize) {
+    let encodings: [&dyn Encoding; 3] = [&Church, &Scott, &Parigot];
+    print!("{:<12}", "operation");
+    for encoding in encodings {
+        print!(" {:>10}", encoding.name());
+    }
+    println!();
+    let table: Vec<Vec<(String, AST, Expected)>> = encodings
+        .iter()
+        .map(|encoding| operations(*encoding))
+        .collect();
+    for row in 0..table[0].len() {
+        print!("{:<12}", table[0][row].0);
+        for (encoding, operations) in encodings.iter().zip(&table) {
+            let (_, ast, expected) = &operations[row];
+            let mut trace = ast.clone().trace(Some(&order));
+            let steps = trace.by_ref().take(fuel).count();
+            let cell = match steps < fuel {
+                true if expected.matches(*encoding, trace.term()) => steps.to_string(),
+                true => "wrong".to_string(),
+                false => format!("> {}", fuel),
+            };
+            print!(" {:>10}", cell);
+        }
+        println!();
+    }
+}
@@ -0,0 +1,488 @@
+use crate::ast::AST;
+use crate::backend::Backend;
+use crate::beta::{EvalOrder, ReductionLimits, ReductionResult};
+use crate::cli::bench::{bench, compare_encodings};
+use crate::encodings::EncodingKind;
+use crate::environment::Environment;
+use crate::eta::EtaReduction;
+use crate::parser::Statement;
+use crate::printer::Printer;
+use crate::proof::{Proof, Prop};
+use crate::repl::Repl;
+use crate::types::TypeInference;
+use std::fs;
+use std::process::ExitCode;
+use std::time::Duration;
+
+pub const USAGE: &str = "\
+Usage:
+  lambda                                         start the interactive REPL
+  lambda run <file> [--order <order>] [--backend <backend>] [--steps <n>|off]
+             [--timeout <seconds>|off] [--eta] [--prelude] [--decode] [--encoding <encoding>]
+             [--no-collapse] [--all-parens]
+                                                 evaluate every expression in a file, at most
+                                                 100000 steps and 10 seconds each unless the
+                                                 steps or the timeout are off, nested lambdas
+                                                 are printed as one unless not collapsed
+  lambda type <file> [--prelude]                 infer the type of every expression in a file
+  lambda prove <proposition> <term>              check the term is a proof of the proposition
+  lambda bench [--timeout <seconds>]             compare the tree and nbe backends
+  lambda encodings [--order <order>] [--steps <n>]
+                                                 compare reduction steps of encodings
+  lambda help                                    show this message
+
+Orders: normal (default), applicative, lazy
+Backends: tree (default), krivine, cek, nbe, vm
+Encodings: church (default), scott, parigot";
+
+/// The step budget of every expression when none is given on the command line
+pub const DEFAULT_FUEL: usize = 100_000;
+
+/// The time limit of every expression when none is given on the command line
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Type represents the subcommands of the command line
+///  - *Repl*: start an interactive session
+///  - *Run*: evaluate a program file with the given options
+///  - *Type*: infer the types of the expressions of a program file
+///  - *Prove*: check a term is a proof of a proposition
+///  - *Bench*: time the normalization of Church numeral arithmetic on two backends
+///  - *Encodings*: count the reduction steps of operations in the Church, Scott and Parigot encodings
+///  - *Help*: print the usage
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cli {
+    Repl,
+    Run { path: String, options: RunOptions },
+    Type { path: String, prelude: bool },
+    Prove { prop: String, term: String },
+    Bench { timeout: Duration },
+    Encodings { order: EvalOrder, fuel: usize },
+    Help,
+}
+
+/// Type represents the options of running a program file
+///  - *order*, *backend*: how the expressions are reduced
+///  - *fuel*: the step budget, *None* means unlimited which has to be asked for
+///  - *timeout*: the time limit of every expression, *None* means unlimited
+///  - *eta*: eta reduce the normal forms
+///  - *prelude*: start with the definitions of the prelude
+///  - *decode*: print the values the normal forms encode next to them
+///  - *encoding*: the encoding the numbers and lists are decoded from
+///  - *printer*: how the normal forms are printed
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunOptions {
+    pub order: EvalOrder,
+    pub backend: Backend,
+    pub fuel: Option<usize>,
+    pub timeout: Option<Duration>,
+    pub eta: bool,
+    pub prelude: bool,
+    pub decode: bool,
+    pub encoding: EncodingKind,
+    pub printer: Printer,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        RunOptions {
+            order: EvalOrder::default(),
+            backend: Backend::default(),
+            fuel: Some(DEFAULT_FUEL),
+            timeout: Some(DEFAULT_TIMEOUT),
+            eta: false,
+            prelude: false,
+            decode: false,
+            encoding: EncodingKind::default(),
+            printer: Printer::default(),
+        }
+    }
+}
+
+impl Cli {
+    /// Parse the command line arguments, without the name of the program
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Cli, String> {
+        let mut args = args.into_iter();
+        match args.next().as_deref() {
+            None | Some("repl") => Ok(Cli::Repl),
+            Some("help") | Some("--help") | Some("-h") => Ok(Cli::Help),
+            Some("run") => {
+                let (mut path, mut options) = (None, RunOptions::default());
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--order" => options.order = Cli::value(&mut args, &arg)?.parse()?,
+                        "--backend" => options.backend = Cli::value(&mut args, &arg)?.parse()?,
+                        "--steps" => {
+                            let steps = Cli::value(&mut args, &arg)?;
+                            options.fuel = match steps.as_str() {
+                                "off" => None,
+                                steps => Some(steps.parse().map_err(|_| {
+                                    format!(
+                                        "Expected a number of steps or 'off', found '{}'",
+                                        steps
+                                    )
+                                })?),
+                            }
+                        }
+                        "--timeout" => {
+                            let seconds = Cli::value(&mut args, &arg)?;
+                            options.timeout = match seconds.as_str() {
+                                "off" => None,
+                                seconds => {
+                                    Some(Duration::from_secs(seconds.parse().map_err(|_| {
+                                        format!(
+                                            "Expected a number of seconds or 'off', found '{}'",
+                                            seconds
+                                        )
+                                    })?))
+                                }
+                            }
+                        }
+                        "--eta" => options.eta = true,
+                        "--prelude" => options.prelude = true,
+                        "--decode" => options.decode = true,
+                        "--encoding" => options.encoding = Cli::value(&mut args, &arg)?.parse()?,
+                        "--no-collapse" => options.printer.collapse_abstractions = false,
+                        "--all-parens" => options.printer.minimal_parens = false,
+                        _ if path.is_none() && !arg.starts_with("--") => path = Some(arg),
+                        _ => return Err(format!("Unexpected argument '{}'", arg)),
+                    }
+                }
+                match path {
+                    Some(path) => Ok(Cli::Run { path, options }),
+                    None => Err("Missing the file to run".to_string()),
+                }
+            }
+            Some("type") => {
+                let (mut path, mut prelude) = (None, false);
+                for arg in args {
+                    match arg.as_str() {
+                        "--prelude" => prelude = true,
+                        _ if path.is_none() && !arg.starts_with("--") => path = Some(arg),
+                        _ => return Err(format!("Unexpected argument '{}'", arg)),
+                    }
+                }
+                match path {
+                    Some(path) => Ok(Cli::Type { path, prelude }),
+                    None => Err("Missing the file to type".to_string()),
+                }
+            }
+            Some("prove") => match (args.next(), args.next(), args.next()) {
+                (Some(prop), Some(term), None) => Ok(Cli::Prove { prop, term }),
+                (_, _, Some(arg)) => Err(format!("Unexpected argument '{}'", arg)),
+                _ => Err("Expected a proposition and a term".to_string()),
+            },
+            Some("bench") => {
+                let mut timeout = Duration::from_secs(10);
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--timeout" => {
+                            let seconds = Cli::value(&mut args, &arg)?;
+                            timeout = Duration::from_secs(seconds.parse().map_err(|_| {
+                                format!("Expected a number of seconds, found '{}'", seconds)
+                            })?)
+                        }
+                        _ => return Err(format!("Unexpected argument '{}'", arg)),
+                    }
+                }
+                Ok(Cli::Bench { timeout })
+            }
+            Some("encodings") => {
+                let (mut order, mut fuel) = (EvalOrder::Normal, DEFAULT_FUEL);
+                while let Some(arg) = args.next() {
+                    match arg.as_str() {
+                        "--order" => order = Cli::value(&mut args, &arg)?.parse()?,
+                        "--steps" => {
+                            let steps = Cli::value(&mut args, &arg)?;
+                            fuel = steps.parse().map_err(|_| {
+                                format!("Expected a number of steps, found '{}'", steps)
+                            })?
+                        }
+                        _ => return Err(format!("Unexpected argument '{}'", arg)),
+                    }
+                }
+                Ok(Cli::Encodings { order, fuel })
+            }
+            Some(command) => Err(format!("Unknown command '{}'", command)),
+        }
+    }
+
+    /// Read the value of an option
+    fn value(args: &mut impl Iterator<Item = String>, option: &str) -> Result<String, String> {
+        args.next()
+            .ok_or_else(|| format!("Missing a value for '{}'", option))
+    }
+
+    pub fn execute(self) -> ExitCode {
+        match self {
+            Cli::Repl => match Repl::new().run() {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    ExitCode::FAILURE
+                }
+            },
+            Cli::Run { path, options } => run(&path, &options),
+            Cli::Type { path, prelude } => infer_types(&path, prelude),
+            Cli::Prove { prop, term } => prove(&prop, &term),
+            Cli::Bench { timeout } => {
+                bench(timeout);
+                ExitCode::SUCCESS
+            }
+            Cli::Encodings { order, fuel } => {
+                compare_encodings(order, fuel);
+                ExitCode::SUCCESS
+            }
+            Cli::Help => {
+                println!("{}", USAGE);
+                ExitCode::SUCCESS
+            }
+        }
+    }
+}
+
+/// Read the statements of a program file, reporting the errors with the path
+fn read_program(path: &str) -> Result<(String, Vec<Statement>), ExitCode> {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("Cannot read '{}': {}", path, err);
+            return Err(ExitCode::FAILURE);
+        }
+    };
+    match Statement::eval_program(&source) {
+        Ok(statements) => Ok((source, statements)),
+        Err(err) => {
+            eprintln!("{}: {}", path, err);
+            Err(ExitCode::FAILURE)
+        }
+    }
+}
+
+/// Evaluate a program file. Definitions are collected in order and every expression
+/// is reduced with the definitions made before it, starting with the prelude if asked for.
+/// Every expression is reported, the run fails if any of them is not normalized within the limits
+fn run(path: &str, options: &RunOptions) -> ExitCode {
+    let statements = match read_program(path) {
+        Ok((_, statements)) => statements,
+        Err(code) => return code,
+    };
+    let limits = ReductionLimits {
+        fuel: options.fuel,
+        deadline: None,
+    };
+    let mut env = match options.prelude {
+        true => Environment::prelude(),
+        false => Environment::new(),
+    };
+    let mut code = ExitCode::SUCCESS;
+    for statement in statements {
+        match statement {
+            Statement::Definition(name, ast) => env = env.with(name, ast),
+            Statement::Expression(ast) => {
+                // Every expression gets the whole time limit
+                let limits = match options.timeout {
+                    Some(timeout) => limits.with_timeout(timeout),
+                    None => limits,
+                };
+                let result = options
+                    .backend
+                    .reduce(env.resolve(ast), &options.order, &limits);
+                let result = match result {
+                    ReductionResult::Normalized(ast) if options.eta => {
+                        ReductionResult::Normalized(ast.eta_reduce())
+                    }
+                    _ => result,
+                };
+                match result {
+                    ReductionResult::Normalized(ast) if options.decode => {
+                        match ast.decode(options.encoding.encoding()) {
+                            Some(value) => {
+                                println!("{}    = {}", options.printer.print(&ast), value)
+                            }
+                            None => println!("{}", options.printer.print(&ast)),
+                        }
+                    }
+                    ReductionResult::Normalized(ast) => println!("{}", options.printer.print(&ast)),
+                    // The machines stop at the weak head, which is what they were asked for
+                    result @ ReductionResult::WeakHead(_) => {
+                        println!("{}", result.show(&options.printer))
+                    }
+                    result => {
+                        eprintln!("{}", result.show(&options.printer));
+                        code = ExitCode::FAILURE;
+                    }
+                }
+            }
+        }
+    }
+    code
+}
+
+/// Infer the type of every expression of a program file with the definitions made before it.
+/// Every expression is reported, the run fails if any of them has no type
+fn infer_types(path: &str, prelude: bool) -> ExitCode {
+    let (source, statements) = match read_program(path) {
+        Ok(program) => program,
+        Err(code) => return code,
+    };
+    let mut env = match prelude {
+        true => Environment::prelude(),
+        false => Environment::new(),
+    };
+    let mut code = ExitCode::SUCCESS;
+    for statement in statements {
+        match statement {
+            Statement::Definition(name, ast) => env = env.with(name, ast),
+            Statement::Expression(ast) => match env.resolve(ast.clone()).infer_type() {
+                Ok(ty) => println!("{} : {}", ast, ty),
+                Err(err) => {
+                    eprintln!("{}", err.render(&source));
+                    code = ExitCode::FAILURE;
+                }
+            },
+        }
+    }
+    code
+}
+
+/// Check the term is a proof of the proposition, reporting the subgoal that fails
+fn prove(prop: &str, term: &str) -> ExitCode {
+    let (prop, ast) = match (Prop::eval(prop), AST::eval(term)) {
+        (Ok(prop), Ok(ast)) => (prop, ast),
+        (Err(err), _) | (_, Err(err)) => {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    match ast.prove(&prop) {
+        Ok(()) => {
+            println!("'{}' is a proof of {}", ast, prop);
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("{}", err.render(term));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cli, RunOptions, DEFAULT_FUEL, DEFAULT_TIMEOUT};
+    use crate::encodings::EncodingKind;
+    use crate::printer::Printer;
+    use std::time::Duration;
+
+    fn parse(args: &[&str]) -> Result<Cli, String> {
+        Cli::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn run_has_finite_fuel_by_default() {
+        let options = RunOptions {
+            fuel: Some(DEFAULT_FUEL),
+            timeout: Some(DEFAULT_TIMEOUT),
+            ..RunOptions::default()
+        };
+        assert_eq!(
+            parse(&["run", "main.lam"]),
+            Ok(Cli::Run {
+                path: "main.lam".to_string(),
+                options
+            })
+        );
+    }
+
+    #[test]
+    fn run_steps() {
+        let options = RunOptions {
+            fuel: Some(10),
+            ..RunOptions::default()
+        };
+        assert_eq!(
+            parse(&["run", "main.lam", "--steps", "10"]),
+            Ok(Cli::Run {
+                path: "main.lam".to_string(),
+                options
+            })
+        );
+    }
+
+    #[test]
+    fn run_unlimited_steps() {
+        let options = RunOptions {
+            fuel: None,
+            ..RunOptions::default()
+        };
+        assert_eq!(
+            parse(&["run", "main.lam", "--steps", "off"]),
+            Ok(Cli::Run {
+                path: "main.lam".to_string(),
+                options
+            })
+        );
+        assert!(parse(&["run", "main.lam", "--steps", "many"]).is_err());
+    }
+
+    #[test]
+    fn run_timeout() {
+        let options = RunOptions {
+            timeout: Some(Duration::from_secs(3)),
+            ..RunOptions::default()
+        };
+        assert_eq!(
+            parse(&["run", "main.lam", "--timeout", "3"]),
+            Ok(Cli::Run {
+                path: "main.lam".to_string(),
+                options
+            })
+        );
+        let options = RunOptions {
+            timeout: None,
+            ..RunOptions::default()
+        };
+        assert_eq!(
+            parse(&["run", "main.lam", "--timeout", "off"]),
+            Ok(Cli::Run {
+                path: "main.lam".to_string(),
+                options
+            })
+        );
+        assert!(parse(&["run", "main.lam", "--timeout", "soon"]).is_err());
+    }
+
+    #[test]
+    fn run_printer() {
+        let options = RunOptions {
+            printer: Printer {
+                collapse_abstractions: false,
+                minimal_parens: false,
+            },
+            ..RunOptions::default()
+        };
+        assert_eq!(
+            parse(&["run", "main.lam", "--no-collapse", "--all-parens"]),
+            Ok(Cli::Run {
+                path: "main.lam".to_string(),
+                options
+            })
+        );
+    }
+
+    #[test]
+    fn run_encoding() {
+        let options = RunOptions {
+            decode: true,
+            encoding: EncodingKind::Scott,
+            ..RunOptions::default()
+        };
+        assert_eq!(
+            parse(&["run", "main.lam", "--decode", "--encoding", "scott"]),
+            Ok(Cli::Run {
+                path: "main.lam".to_string(),
+                options
+            })
+        );
+        assert!(parse(&["run", "main.lam", "--encoding", "binary"]).is_err());
+    }
+}
@@ -0,0 +1,5 @@
+//! Command line interface of the interpreter
+pub mod bench;
+pub mod cli;
+
+pub use self::cli::Cli;
@@ -0,0 +1,317 @@
+use crate::ast::Term;
+use crate::ast::AST;
+use crate::variables::VarGen;
+use std::collections::HashSet;
+use std::fmt;
+use std::rc::Rc;
+
+/// Type represents a term where bound variables are replaced by de Bruijn indices,
+/// so alpha-equivalent terms have the same representation
+///  - *Bound*: variable bound by the n-th enclosing abstraction, counting from 0
+///  - *Free*: free variable, kept by its name
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub enum DeBruijn {
+    Bound(usize),
+    Free(String),
+    Abstr(Rc<DeBruijn>),
+    Apply(Rc<DeBruijn>, Rc<DeBruijn>),
+}
+
+impl AST {
+    /// Convert the term into its nameless representation
+    pub fn to_de_bruijn(&self) -> DeBruijn {
+        self.to_de_bruijn_in(&mut Vec::new())
+    }
+
+    /// Conversion with the names of the enclosing binders, the innermost one last
+    fn to_de_bruijn_in(&self, binders: &mut Vec<String>) -> DeBruijn {
+        match &self.term {
+            Term::Var(s) => match binders.iter().rev().position(|name| name == s) {
+                Some(index) => DeBruijn::Bound(index),
+                None => DeBruijn::Free(s.clone()),
+            },
+            Term::Abstr(param, body) => {
+                let name = match &param.term {
+                    Term::Var(s) => s.clone(),
+                    _ => panic!("Abstraction parameter must be a variable"),
+                };
+                binders.push(name);
+                let body = body.to_de_bruijn_in(binders);
+                binders.pop();
+                DeBruijn::Abstr(Rc::new(body))
+            }
+            Term::Apply(f, arg) => DeBruijn::Apply(
+                Rc::new(f.to_de_bruijn_in(binders)),
+                Rc::new(arg.to_de_bruijn_in(binders)),
+            ),
+        }
+    }
+}
+
+impl DeBruijn {
+    /// Convert the term back into a named one, drawing the names of binders from the generator.
+    /// Names of free variables are skipped so they cannot be captured. Indices that point
+    /// outside of the term get names of their own, as if the term was under more binders
+    pub fn to_named(&self, names: &VarGen) -> AST {
+        let free = self.free_vars();
+        let mut available = names.clone().filter(|name| !free.contains(name));
+        let outer: Vec<String> = (&mut available).take(self.loose_depth(0)).collect();
+        let mut binders = outer.clone();
+        self.to_named_in(&mut binders, &mut outer.clone(), &mut available)
+    }
+
+    /// Number of binders missing around the term for all its indices to be bound,
+    /// with the given number of binders already around it
+    fn loose_depth(&self, depth: usize) -> usize {
+        match self {
+            DeBruijn::Bound(index) => (index + 1).saturating_sub(depth),
+            DeBruijn::Free(_) => 0,
+            DeBruijn::Abstr(body) => body.loose_depth(depth + 1),
+            DeBruijn::Apply(f, arg) => f.loose_depth(depth).max(arg.loose_depth(depth)),
+        }
+    }
+
+    /// Conversion with the names of the enclosing binders, the innermost one last.
+    /// The name of a binder depends only on its depth, drawn from the generator when first needed
+    fn to_named_in(
+        &self,
+        binders: &mut Vec<String>,
+        depth_names: &mut Vec<String>,
+        available: &mut impl Iterator<Item = String>,
+    ) -> AST {
+        match self {
+            DeBruijn::Bound(index) => AST::var(binders[binders.len() - 1 - index].clone()),
+            DeBruijn::Free(s) => AST::var(s.clone()),
+            DeBruijn::Abstr(body) => {
+                if depth_names.len() <= binders.len() {
+                    let name = available.next().expect("Ran out of variable names");
+                    depth_names.push(name);
+                }
+                let name = depth_names[binders.len()].clone();
+                binders.push(name.clone());
+                let body = body.to_named_in(binders, depth_names, available);
+                binders.pop();
+                AST::abstr(AST::var(name), body)
+            }
+            DeBruijn::Apply(f, arg) => AST::apply(
+                f.to_named_in(binders, depth_names, available),
+                arg.to_named_in(binders, depth_names, available),
+            ),
+        }
+    }
+
+    /// Names of all free variables in the term
+    pub fn free_vars(&self) -> HashSet<String> {
+        match self {
+            DeBruijn::Bound(_) => HashSet::new(),
+            DeBruijn::Free(s) => HashSet::from([s.clone()]),
+            DeBruijn::Abstr(body) => body.free_vars(),
+            DeBruijn::Apply(f, arg) => &f.free_vars() | &arg.free_vars(),
+        }
+    }
+
+    /// Whether the variable bound the given number of binders outside of the term occurs in it
+    pub fn occurs(&self, index: usize) -> bool {
+        match self {
+            DeBruijn::Bound(i) => *i == index,
+            DeBruijn::Free(_) => false,
+            DeBruijn::Abstr(body) => body.occurs(index + 1),
+            DeBruijn::Apply(f, arg) => f.occurs(index) || arg.occurs(index),
+        }
+    }
+
+    /// Shift the indices of variables bound outside of the term, i.e. at least the cutoff, by d
+    pub fn shift(&self, d: isize, cutoff: usize) -> DeBruijn {
+        match self {
+            DeBruijn::Bound(index) if *index >= cutoff => DeBruijn::Bound(
+                index
+                    .checked_add_signed(d)
+                    .expect("Negative de Bruijn index"),
+            ),
+            DeBruijn::Bound(_) | DeBruijn::Free(_) => self.clone(),
+            DeBruijn::Abstr(body) => DeBruijn::Abstr(Rc::new(body.shift(d, cutoff + 1))),
+            DeBruijn::Apply(f, arg) => {
+                DeBruijn::Apply(Rc::new(f.shift(d, cutoff)), Rc::new(arg.shift(d, cutoff)))
+            }
+        }
+    }
+
+    /// Substitute the variable with the given index by the term. No renaming is ever needed
+    pub fn substitute(&self, index: usize, term: &DeBruijn) -> DeBruijn {
+        match self {
+            DeBruijn::Bound(i) if *i == index => term.clone(),
+            DeBruijn::Bound(_) | DeBruijn::Free(_) => self.clone(),
+            // Under the binder, the index and the free variables of the term move by one
+            DeBruijn::Abstr(body) => {
+                DeBruijn::Abstr(Rc::new(body.substitute(index + 1, &term.shift(1, 0))))
+            }
+            DeBruijn::Apply(f, arg) => DeBruijn::Apply(
+                Rc::new(f.substitute(index, term)),
+                Rc::new(arg.substitute(index, term)),
+            ),
+        }
+    }
+
+    /// Contract a redex, i.e. the application of an abstraction with the given body to the argument
+    pub fn contract(body: &DeBruijn, arg: &DeBruijn) -> DeBruijn {
+        body.substitute(0, &arg.shift(1, 0)).shift(-1, 0)
+    }
+
+    /// One step of a normal order reduction, contracting the leftmost outermost redex.
+    /// Returns None if the term is in normal form
+    pub fn beta_step(&self) -> Option<DeBruijn> {
+        match self {
+            DeBruijn::Bound(_) | DeBruijn::Free(_) => None,
+            DeBruijn::Abstr(body) => body.beta_step().map(|body| DeBruijn::Abstr(Rc::new(body))),
+            DeBruijn::Apply(f, arg) => match f.as_ref() {
+                DeBruijn::Abstr(body) => Some(DeBruijn::contract(body, arg)),
+                _ => match f.beta_step() {
+                    Some(f) => Some(DeBruijn::Apply(Rc::new(f), arg.clone())),
+                    None => arg
+                        .beta_step()
+                        .map(|arg| DeBruijn::Apply(f.clone(), Rc::new(arg))),
+                },
+            },
+        }
+    }
+
+    /// The normal order reduction loop that may also never terminate
+    pub fn reduce(self) -> DeBruijn {
+        let mut term = self;
+        while let Some(next) = term.beta_step() {
+            term = next;
+        }
+        term
+    }
+}
+
+impl fmt::Display for DeBruijn {
+    /// Print the term with 'λ' for binders and indices for bound variables, i.e. λ λ 1 0
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeBruijn::Bound(index) => write!(f, "{}", index),
+            DeBruijn::Free(s) => write!(f, "{}", s),
+            DeBruijn::Abstr(body) => write!(f, "λ {}", body),
+            DeBruijn::Apply(func, arg) => {
+                match func.as_ref() {
+                    DeBruijn::Abstr(_) => write!(f, "({})", func)?,
+                    _ => write!(f, "{}", func)?,
+                }
+                match arg.as_ref() {
+                    DeBruijn::Apply(_, _) | DeBruijn::Abstr(_) => write!(f, " ({})", arg),
+                    _ => write!(f, " {}", arg),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DeBruijn;
+    use crate::ast::AST;
+    use crate::variables::VarGen;
+    use std::rc::Rc;
+
+    fn abstr(body: DeBruijn) -> DeBruijn {
+        DeBruijn::Abstr(Rc::new(body))
+    }
+
+    fn apply(f: DeBruijn, arg: DeBruijn) -> DeBruijn {
+        DeBruijn::Apply(Rc::new(f), Rc::new(arg))
+    }
+
+    fn parse(input: &str) -> AST {
+        AST::eval(input).unwrap()
+    }
+
+    #[test]
+    fn to_de_bruijn() {
+        let expected = abstr(abstr(apply(
+            apply(DeBruijn::Bound(1), DeBruijn::Bound(0)),
+            DeBruijn::Free("z".to_string()),
+        )));
+        assert_eq!(parse("$x, y -> x y z").to_de_bruijn(), expected);
+        // The inner binder shadows the outer one
+        let expected = abstr(abstr(DeBruijn::Bound(0)));
+        assert_eq!(parse("$x -> $x -> x").to_de_bruijn(), expected);
+    }
+
+    #[test]
+    fn round_trip() {
+        for input in [
+            "$x -> x",
+            "$x, y -> y x",
+            "$x -> $x -> x",
+            "$f -> ($x -> f (x x)) ($x -> f (x x))",
+            "$x -> a b x",
+            "a ($x -> x) b",
+        ] {
+            let nameless = parse(input).to_de_bruijn();
+            let named = nameless.to_named(&VarGen::new());
+            assert_eq!(named.to_de_bruijn(), nameless, "{}", input);
+            assert_eq!(named, parse(input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn to_named_avoids_free_names() {
+        let named = parse("$x -> a x").to_de_bruijn().to_named(&VarGen::new());
+        assert_eq!(named.to_string(), "$b -> a b");
+    }
+
+    #[test]
+    fn to_named_loose_indices() {
+        // Index 1 points outside of the term, so it gets a name of its own
+        let named = abstr(apply(DeBruijn::Bound(0), DeBruijn::Bound(1))).to_named(&VarGen::new());
+        assert_eq!(named.to_string(), "$b -> b a");
+    }
+
+    #[test]
+    fn shift_free_indices() {
+        // Only indices that are not bound inside the term move
+        let term = apply(
+            DeBruijn::Bound(0),
+            abstr(apply(DeBruijn::Bound(0), DeBruijn::Bound(1))),
+        );
+        let expected = apply(
+            DeBruijn::Bound(2),
+            abstr(apply(DeBruijn::Bound(0), DeBruijn::Bound(3))),
+        );
+        assert_eq!(term.shift(2, 0), expected);
+        assert_eq!(expected.shift(-2, 0), term);
+        // Indices below the cutoff are left alone
+        assert_eq!(DeBruijn::Bound(0).shift(1, 1), DeBruijn::Bound(0));
+    }
+
+    #[test]
+    fn substitute_free_indices() {
+        let term = abstr(apply(DeBruijn::Bound(1), DeBruijn::Bound(0)));
+        // Under the binder the index and the term both move by one
+        let expected = abstr(apply(DeBruijn::Bound(4), DeBruijn::Bound(0)));
+        assert_eq!(term.substitute(0, &DeBruijn::Bound(3)), expected);
+        // Other indices are not touched
+        assert_eq!(term.substitute(1, &DeBruijn::Bound(3)), term);
+    }
+
+    #[test]
+    fn contract_does_not_capture() {
+        // ($x, y -> x) y -> $z -> y
+        let body = abstr(DeBruijn::Bound(1));
+        let arg = DeBruijn::Free("y".to_string());
+        assert_eq!(DeBruijn::contract(&body, &arg), abstr(arg.clone()));
+        // A loose index in the argument is still loose in the result
+        let result = DeBruijn::contract(&body, &DeBruijn::Bound(0));
+        assert_eq!(result, abstr(DeBruijn::Bound(1)));
+    }
+
+    #[test]
+    fn equality_up_to_alpha() {
+        assert_eq!(parse("$x -> x"), parse("$y -> y"));
+        assert_eq!(parse("$x, y -> x y"), parse("$y, x -> y x"));
+        assert_eq!(parse("$x -> $x -> x"), parse("$a -> $b -> b"));
+        assert_ne!(parse("$x, y -> x"), parse("$x, y -> y"));
+        assert_ne!(parse("$x -> a"), parse("$x -> b"));
+        assert_ne!(parse("$x -> y"), parse("$y -> y"));
+    }
+}
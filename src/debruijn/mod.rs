@@ -0,0 +1,4 @@
+//! This module handles the nameless representation of terms with de Bruijn indices
+pub mod debruijn;
+
+pub use self::debruijn::DeBruijn;
@@ -0,0 +1,33 @@
+use crate::ast::AST;
+use crate::debruijn::DeBruijn;
+use crate::variables::VarGen;
+use std::rc::Rc;
+
+/// A variable with the name
+pub fn var(name: &str) -> AST {
+    AST::var(name.to_string())
+}
+
+/// An abstraction over all the parameters, the first one outermost
+pub fn lambda(params: &[&str], body: AST) -> AST {
+    params
+        .iter()
+        .rev()
+        .fold(body, |body, param| AST::abstr(var(param), body))
+}
+
+/// The function applied to all the arguments, the first one innermost
+pub fn apply(f: AST, args: impl IntoIterator<Item = AST>) -> AST {
+    args.into_iter().fold(f, AST::apply)
+}
+
+/// The nameless term under two abstractions, with names that cannot capture its free variables
+pub fn nameless_under_two(body: DeBruijn) -> AST {
+    DeBruijn::Abstr(Rc::new(DeBruijn::Abstr(Rc::new(body)))).to_named(&VarGen::new())
+}
+
+/// The nameless application of the function to all the arguments
+pub fn nameless_apply(f: DeBruijn, args: impl IntoIterator<Item = DeBruijn>) -> DeBruijn {
+    args.into_iter()
+        .fold(f, |f, arg| DeBruijn::Apply(Rc::new(f), Rc::new(arg)))
+}
@@ -0,0 +1,264 @@
+use crate::ast::AST;
+use crate::debruijn::DeBruijn;
+use crate::encodings::build::{apply, lambda, nameless_apply, nameless_under_two, var};
+use crate::encodings::Encoding;
+
+/// Definitions of the Church encodings in the source language. Those that are also built below
+/// are the same terms
+pub const PRELUDE: &str = include_str!("prelude.lam");
+
+/// Church numeral of n, i.e. $f, x -> f (f (... x))
+pub fn numeral(n: u64) -> AST {
+    let body = (0..n).fold(var("x"), |body, _| AST::apply(var("f"), body));
+    lambda(&["f", "x"], body)
+}
+
+/// Successor of a numeral, $n, f, x -> f (n f x)
+pub fn succ() -> AST {
+    let body = AST::apply(var("f"), apply(var("n"), [var("f"), var("x")]));
+    lambda(&["n", "f", "x"], body)
+}
+
+/// Sum of two numerals, $m, n, f, x -> m f (n f x)
+pub fn add() -> AST {
+    let body = apply(var("m"), [var("f"), apply(var("n"), [var("f"), var("x")])]);
+    lambda(&["m", "n", "f", "x"], body)
+}
+
+/// Product of two numerals, $m, n, f -> m (n f)
+pub fn mul() -> AST {
+    let body = AST::apply(var("m"), AST::apply(var("n"), var("f")));
+    lambda(&["m", "n", "f"], body)
+}
+
+/// Power of two numerals, $b, e -> e b
+pub fn pow() -> AST {
+    lambda(&["b", "e"], AST::apply(var("e"), var("b")))
+}
+
+/// Predecessor of a numeral, zero for zero, $n, f, x -> n ($g, h -> h (g f)) ($u -> x) ($u -> u)
+pub fn pred() -> AST {
+    let step = lambda(
+        &["g", "h"],
+        AST::apply(var("h"), AST::apply(var("g"), var("f"))),
+    );
+    let body = apply(
+        var("n"),
+        [step, lambda(&["u"], var("x")), lambda(&["u"], var("u"))],
+    );
+    lambda(&["n", "f", "x"], body)
+}
+
+/// Test of a numeral for zero, $n -> n ($x -> false) true
+pub fn is_zero() -> AST {
+    let body = apply(var("n"), [lambda(&["x"], boolean(false)), boolean(true)]);
+    lambda(&["n"], body)
+}
+
+/// Church boolean, $a, b -> a for true and $a, b -> b for false
+pub fn boolean(b: bool) -> AST {
+    lambda(&["a", "b"], var(if b { "a" } else { "b" }))
+}
+
+/// Conditional, $p, a, b -> p a b
+pub fn if_then_else() -> AST {
+    lambda(&["p", "a", "b"], apply(var("p"), [var("a"), var("b")]))
+}
+
+/// Pair constructor, $a, b, s -> s a b
+pub fn pair() -> AST {
+    lambda(&["a", "b", "s"], apply(var("s"), [var("a"), var("b")]))
+}
+
+/// First component of a pair, $p -> p ($a, b -> a)
+pub fn fst() -> AST {
+    lambda(&["p"], AST::apply(var("p"), boolean(true)))
+}
+
+/// Second component of a pair, $p -> p ($a, b -> b)
+pub fn snd() -> AST {
+    lambda(&["p"], AST::apply(var("p"), boolean(false)))
+}
+
+/// The pair of two terms
+pub fn make_pair(first: AST, second: AST) -> AST {
+    apply(pair(), [first, second])
+}
+
+/// Empty list, $c, n -> n
+pub fn nil() -> AST {
+    lambda(&["c", "n"], var("n"))
+}
+
+/// List constructor, $h, t, c, n -> c h (t c n)
+pub fn cons() -> AST {
+    let body = apply(var("c"), [var("h"), apply(var("t"), [var("c"), var("n")])]);
+    lambda(&["h", "t", "c", "n"], body)
+}
+
+/// Right fold of a list, $l, f, z -> l f z
+pub fn fold() -> AST {
+    lambda(&["l", "f", "z"], apply(var("l"), [var("f"), var("z")]))
+}
+
+/// The list of the terms in normal form, $c, n -> c x (c y (... n))
+pub fn list(items: Vec<AST>) -> AST {
+    let body = items.iter().rev().fold(DeBruijn::Bound(0), |tail, item| {
+        nameless_apply(DeBruijn::Bound(1), [item.to_de_bruijn().shift(2, 0), tail])
+    });
+    nameless_under_two(body)
+}
+
+/// Church encoding, where a value is its own fold. A number n applies a function n times,
+/// so the predecessor has to rebuild the whole number
+pub struct Church;
+
+impl Encoding for Church {
+    fn name(&self) -> &'static str {
+        "church"
+    }
+
+    fn numeral(&self, n: u64) -> AST {
+        numeral(n)
+    }
+
+    fn as_numeral(&self, ast: &AST) -> Option<u64> {
+        ast.as_church_numeral()
+    }
+
+    fn succ(&self) -> AST {
+        succ()
+    }
+
+    fn pred(&self) -> AST {
+        pred()
+    }
+
+    fn is_zero(&self) -> AST {
+        is_zero()
+    }
+
+    fn nil(&self) -> AST {
+        nil()
+    }
+
+    fn cons(&self) -> AST {
+        cons()
+    }
+
+    fn list(&self, items: Vec<AST>) -> AST {
+        list(items)
+    }
+
+    fn as_list(&self, ast: &AST) -> Option<Vec<AST>> {
+        ast.as_church_list(|item| Some(item.clone()))
+    }
+
+    /// $l -> l ($h, t -> h) nil, the head of the empty list is the empty list
+    fn head(&self) -> AST {
+        lambda(
+            &["l"],
+            apply(var("l"), [lambda(&["h", "t"], var("h")), nil()]),
+        )
+    }
+
+    /// Like the predecessor, the tail rebuilds the list, keeping the previous one in a pair,
+    /// $l -> fst (l ($h, p -> pair (snd p) (cons h (snd p))) (pair nil nil))
+    fn tail(&self) -> AST {
+        let rest = AST::apply(snd(), var("p"));
+        let step = lambda(
+            &["h", "p"],
+            make_pair(rest.clone(), apply(cons(), [var("h"), rest])),
+        );
+        let body = AST::apply(fst(), apply(var("l"), [step, make_pair(nil(), nil())]));
+        lambda(&["l"], body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::beta::BetaReduction;
+    use crate::environment::Environment;
+
+    /// The builder of a definition of the prelude, if there is one
+    fn builder(name: &str) -> Option<AST> {
+        let numerals = [
+            "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+        ];
+        if let Some(n) = numerals.iter().position(|numeral| *numeral == name) {
+            return Some(numeral(n as u64));
+        }
+        match name {
+            "true" => Some(boolean(true)),
+            "false" => Some(boolean(false)),
+            "if" => Some(if_then_else()),
+            "succ" => Some(succ()),
+            "add" => Some(add()),
+            "mul" => Some(mul()),
+            "pow" => Some(pow()),
+            "pred" => Some(pred()),
+            "iszero" => Some(is_zero()),
+            "pair" => Some(pair()),
+            "fst" => Some(fst()),
+            "snd" => Some(snd()),
+            "nil" => Some(nil()),
+            "cons" => Some(cons()),
+            "fold" => Some(fold()),
+            _ => None,
+        }
+    }
+
+    /// Examples of the definitions with the normal forms they reduce to, every definition
+    /// without a builder has at least one
+    const EXAMPLES: [(&str, &str, &str); 14] = [
+        ("if", "if true a b", "a"),
+        ("if", "if false a b", "b"),
+        ("and", "and true false", "false"),
+        ("and", "and true true", "true"),
+        ("or", "or false true", "true"),
+        ("or", "or false false", "false"),
+        ("not", "not true", "false"),
+        ("add", "add two three", "five"),
+        ("sub", "sub five two", "three"),
+        ("sub", "sub two five", "zero"),
+        ("fold", "fold (cons a (cons b nil)) f z", "f a (f b z)"),
+        ("fold", "fold nil f z", "z"),
+        ("isnil", "isnil nil", "true"),
+        ("isnil", "isnil (cons a nil)", "false"),
+    ];
+
+    #[test]
+    fn prelude_matches_builders() {
+        // The definitions refer to the earlier ones, which the environment substitutes
+        for (name, ast) in Environment::prelude().definitions() {
+            match builder(name) {
+                Some(built) => assert_eq!(*ast, built, "{}", name),
+                None => assert!(
+                    EXAMPLES.iter().any(|(defined, _, _)| defined == name),
+                    "No builder or example for the prelude definition '{}'",
+                    name
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn builders_are_beta_equivalent_to_prelude() {
+        let env = Environment::prelude();
+        let built = [("add", add()), ("if", if_then_else()), ("fold", fold())];
+        for (name, ast) in built {
+            let defined = env.resolve(AST::eval(name).unwrap());
+            assert_eq!(ast.reduce(None), defined.reduce(None), "{}", name);
+        }
+    }
+
+    #[test]
+    fn prelude_examples() {
+        let env = Environment::prelude();
+        let eval = |input: &str| env.resolve(AST::eval(input).unwrap());
+        for (_, input, expected) in EXAMPLES {
+            assert_eq!(eval(input).reduce(None), eval(expected), "{}", input);
+        }
+    }
+}
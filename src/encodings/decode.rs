@@ -0,0 +1,182 @@
+use crate::ast::AST;
+use crate::debruijn::DeBruijn;
+use crate::encodings::Encoding;
+use crate::variables::VarGen;
+
+impl DeBruijn {
+    /// The body under exactly two abstractions, if the term has them
+    pub(super) fn under_two(&self) -> Option<&DeBruijn> {
+        match self {
+            DeBruijn::Abstr(body) => match body.as_ref() {
+                DeBruijn::Abstr(body) => Some(body),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The subterm moved out from under the given number of binders,
+    /// if it does not use any of their variables
+    pub(super) fn escape(&self, binders: usize) -> Option<DeBruijn> {
+        match (0..binders).any(|index| self.occurs(index)) {
+            true => None,
+            false => Some(self.shift(-(binders as isize), binders)),
+        }
+    }
+}
+
+impl AST {
+    /// The number the term encodes as a Church numeral, $f, x -> f (f (... x))
+    pub fn as_church_numeral(&self) -> Option<u64> {
+        let mut body = self.to_de_bruijn().under_two()?.clone();
+        let mut n = 0;
+        loop {
+            match body {
+                DeBruijn::Bound(0) => return Some(n),
+                DeBruijn::Apply(f, arg) if *f == DeBruijn::Bound(1) => {
+                    body = arg.as_ref().clone();
+                    n += 1;
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    /// The truth value the term encodes as a Church boolean, $a, b -> a or $a, b -> b
+    pub fn as_church_bool(&self) -> Option<bool> {
+        match self.to_de_bruijn().under_two()? {
+            DeBruijn::Bound(1) => Some(true),
+            DeBruijn::Bound(0) => Some(false),
+            _ => None,
+        }
+    }
+
+    /// The items of a Church list, $c, n -> c x (c y (... n)), each decoded by the decoder.
+    /// Items that refer to the variables of the list itself are not decodable
+    pub fn as_church_list<T>(&self, decoder: impl Fn(&AST) -> Option<T>) -> Option<Vec<T>> {
+        let mut body = self.to_de_bruijn().under_two()?.clone();
+        let mut items = Vec::new();
+        loop {
+            match body {
+                DeBruijn::Bound(0) => return Some(items),
+                DeBruijn::Apply(f, tail) => match f.as_ref() {
+                    DeBruijn::Apply(cons, head) if **cons == DeBruijn::Bound(1) => {
+                        let head = head.escape(2)?.to_named(&VarGen::new());
+                        items.push(decoder(&head)?);
+                        body = tail.as_ref().clone();
+                    }
+                    _ => return None,
+                },
+                _ => return None,
+            }
+        }
+    }
+
+    /// The components of a pair, $s -> s a b
+    pub fn as_pair(&self) -> Option<(AST, AST)> {
+        match self.to_de_bruijn() {
+            DeBruijn::Abstr(body) => match body.as_ref() {
+                DeBruijn::Apply(f, second) => match f.as_ref() {
+                    DeBruijn::Apply(select, first) if **select == DeBruijn::Bound(0) => Some((
+                        first.escape(1)?.to_named(&VarGen::new()),
+                        second.escape(1)?.to_named(&VarGen::new()),
+                    )),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Describe the values the term encodes, reading numbers and lists in the encoding.
+    /// Some terms encode more than one, e.g. $a, b -> b is zero, false and the empty list,
+    /// so all of them are listed
+    pub fn decode(&self, encoding: &dyn Encoding) -> Option<String> {
+        let values: Vec<String> = [
+            encoding.as_numeral(self).map(|n| n.to_string()),
+            self.as_church_bool().map(|b| b.to_string()),
+            self.as_pair().map(|(first, second)| {
+                format!(
+                    "({}, {})",
+                    describe_item(encoding, &first),
+                    describe_item(encoding, &second)
+                )
+            }),
+            describe_list(encoding, self),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        match values.is_empty() {
+            true => None,
+            false => Some(values.join(" or ")),
+        }
+    }
+}
+
+/// Describe the items of a list of the encoding
+fn describe_list(encoding: &dyn Encoding, ast: &AST) -> Option<String> {
+    let items: Vec<String> = encoding
+        .as_list(ast)?
+        .iter()
+        .map(|item| describe_item(encoding, item))
+        .collect();
+    Some(format!("[{}]", items.join(", ")))
+}
+
+/// Describe a component of a pair or a list by the first value it encodes, or as the term itself
+fn describe_item(encoding: &dyn Encoding, ast: &AST) -> String {
+    encoding
+        .as_numeral(ast)
+        .map(|n| n.to_string())
+        .or_else(|| ast.as_church_bool().map(|b| b.to_string()))
+        .or_else(|| {
+            ast.as_pair().map(|(first, second)| {
+                format!(
+                    "({}, {})",
+                    describe_item(encoding, &first),
+                    describe_item(encoding, &second)
+                )
+            })
+        })
+        .or_else(|| describe_list(encoding, ast))
+        .unwrap_or_else(|| ast.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::AST;
+    use crate::encodings::{church, Church, Encoding, Parigot, Scott};
+
+    #[test]
+    fn decode_church() {
+        assert_eq!(Church.numeral(3).decode(&Church), Some("3".to_string()));
+        assert_eq!(
+            church::boolean(true).decode(&Church),
+            Some("true".to_string())
+        );
+        assert_eq!(
+            church::nil().decode(&Church),
+            Some("0 or false or []".to_string())
+        );
+        let list = Church.list(vec![Church.numeral(1), church::boolean(true)]);
+        assert_eq!(list.decode(&Church), Some("[1, true]".to_string()));
+        assert_eq!(AST::eval("x").unwrap().decode(&Church), None);
+    }
+
+    #[test]
+    fn decode_scott() {
+        assert_eq!(Scott.numeral(3).decode(&Scott), Some("3".to_string()));
+        assert_eq!(Scott.numeral(3).decode(&Church), None);
+        let list = Scott.list(vec![Scott.numeral(2), Scott.numeral(0)]);
+        assert_eq!(list.decode(&Scott), Some("[2, 0]".to_string()));
+    }
+
+    #[test]
+    fn decode_parigot() {
+        assert_eq!(Parigot.numeral(2).decode(&Parigot), Some("2".to_string()));
+        let list = Parigot.list(vec![Parigot.numeral(1)]);
+        assert_eq!(list.decode(&Parigot), Some("[1]".to_string()));
+    }
+}
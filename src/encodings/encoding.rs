@@ -0,0 +1,140 @@
+use crate::ast::AST;
+use crate::encodings::{Church, Parigot, Scott};
+use std::fmt;
+use std::str::FromStr;
+
+/// Trait for a way of encoding natural numbers and lists as terms. Every encoding builds
+/// its values and the basic operations on them, and reads the values back from normal forms.
+/// Values are built in normal form, so reducing an operation does not reduce its input first
+pub trait Encoding {
+    /// Name of the encoding to show
+    fn name(&self) -> &'static str;
+
+    fn numeral(&self, n: u64) -> AST;
+    fn as_numeral(&self, ast: &AST) -> Option<u64>;
+    fn succ(&self) -> AST;
+    fn pred(&self) -> AST;
+    fn is_zero(&self) -> AST;
+
+    fn nil(&self) -> AST;
+    fn cons(&self) -> AST;
+    fn list(&self, items: Vec<AST>) -> AST;
+    fn as_list(&self, ast: &AST) -> Option<Vec<AST>>;
+    fn head(&self) -> AST;
+    fn tail(&self) -> AST;
+}
+
+/// Type names the encodings, e.g. to choose the one normal forms are decoded from
+///  - *Church*: values are their own fold
+///  - *Scott*: values are their own case analysis
+///  - *Parigot*: values are their own primitive recursion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EncodingKind {
+    #[default]
+    Church,
+    Scott,
+    Parigot,
+}
+
+impl FromStr for EncodingKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "church" => Ok(EncodingKind::Church),
+            "scott" => Ok(EncodingKind::Scott),
+            "parigot" => Ok(EncodingKind::Parigot),
+            _ => Err(format!(
+                "Unknown encoding '{}', expected church, scott or parigot",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for EncodingKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.encoding().name())
+    }
+}
+
+impl EncodingKind {
+    /// The encoding of the name
+    pub fn encoding(&self) -> &'static dyn Encoding {
+        match self {
+            EncodingKind::Church => &Church,
+            EncodingKind::Scott => &Scott,
+            EncodingKind::Parigot => &Parigot,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EncodingKind;
+    use crate::ast::AST;
+    use crate::beta::BetaReduction;
+    use crate::encodings::build::apply;
+
+    const KINDS: [EncodingKind; 3] = [
+        EncodingKind::Church,
+        EncodingKind::Scott,
+        EncodingKind::Parigot,
+    ];
+
+    #[test]
+    fn names() {
+        for kind in KINDS {
+            assert_eq!(kind.to_string().parse(), Ok(kind));
+        }
+    }
+
+    #[test]
+    fn numerals() {
+        for kind in KINDS {
+            let encoding = kind.encoding();
+            for n in 0..5 {
+                let numeral = encoding.numeral(n);
+                assert_eq!(encoding.as_numeral(&numeral), Some(n), "{}", kind);
+                let succ = AST::apply(encoding.succ(), numeral.clone()).reduce(None);
+                assert_eq!(encoding.as_numeral(&succ), Some(n + 1), "{}", kind);
+                let pred = AST::apply(encoding.pred(), numeral.clone()).reduce(None);
+                assert_eq!(
+                    encoding.as_numeral(&pred),
+                    Some(n.saturating_sub(1)),
+                    "{}",
+                    kind
+                );
+                let is_zero = AST::apply(encoding.is_zero(), numeral).reduce(None);
+                assert_eq!(is_zero.as_church_bool(), Some(n == 0), "{}", kind);
+            }
+        }
+    }
+
+    #[test]
+    fn lists() {
+        for kind in KINDS {
+            let encoding = kind.encoding();
+            let items: Vec<AST> = [3, 1, 2].map(|n| encoding.numeral(n)).to_vec();
+            let list = encoding.list(items.clone());
+            assert_eq!(encoding.as_list(&list), Some(items.clone()), "{}", kind);
+            assert_eq!(encoding.as_list(&encoding.nil()), Some(vec![]), "{}", kind);
+
+            let cons = apply(encoding.cons(), [encoding.numeral(0), list.clone()]).reduce(None);
+            let mut longer = vec![encoding.numeral(0)];
+            longer.extend(items.clone());
+            assert_eq!(cons, encoding.list(longer), "{}", kind);
+
+            let head = AST::apply(encoding.head(), list.clone()).reduce(None);
+            assert_eq!(encoding.as_numeral(&head), Some(3), "{}", kind);
+
+            let tail = AST::apply(encoding.tail(), list).reduce(None);
+            assert_eq!(
+                encoding.as_list(&tail),
+                Some(items[1..].to_vec()),
+                "{}",
+                kind
+            );
+        }
+    }
+}
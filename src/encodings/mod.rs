@@ -0,0 +1,12 @@
+//! This module builds the common data encodings of the lambda calculus as terms
+pub mod build;
+pub mod church;
+pub mod decode;
+pub mod encoding;
+pub mod parigot;
+pub mod scott;
+
+pub use self::church::{Church, PRELUDE};
+pub use self::encoding::{Encoding, EncodingKind};
+pub use self::parigot::Parigot;
+pub use self::scott::Scott;
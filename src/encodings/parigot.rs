@@ -0,0 +1,127 @@
+use crate::ast::AST;
+use crate::debruijn::DeBruijn;
+use crate::encodings::build::{apply, lambda, nameless_apply, nameless_under_two, var};
+use crate::encodings::church::boolean;
+use crate::encodings::Encoding;
+use crate::variables::VarGen;
+
+/// Parigot encoding, which combines the Church and Scott encodings. A successor carries both
+/// its predecessor and the result of the recursion on it, $s, z -> s n (n s z)
+pub struct Parigot;
+
+impl Encoding for Parigot {
+    fn name(&self) -> &'static str {
+        "parigot"
+    }
+
+    /// $s, z -> s n' (s n'' (... z)), where the recursion part n s z is already reduced
+    /// to the body of n
+    fn numeral(&self, n: u64) -> AST {
+        (0..n).fold(lambda(&["s", "z"], var("z")), |pred, _| {
+            let pred = pred.to_de_bruijn();
+            let recursion = pred
+                .under_two()
+                .expect("Numerals have two parameters")
+                .clone();
+            nameless_under_two(nameless_apply(DeBruijn::Bound(1), [pred, recursion]))
+        })
+    }
+
+    /// The number is read from the predecessors, the recursion part is not checked
+    fn as_numeral(&self, ast: &AST) -> Option<u64> {
+        let mut term = ast.to_de_bruijn();
+        let mut n = 0;
+        loop {
+            match term.under_two()? {
+                DeBruijn::Bound(0) => return Some(n),
+                DeBruijn::Apply(f, _) => match f.as_ref() {
+                    DeBruijn::Apply(s, pred) if **s == DeBruijn::Bound(1) => {
+                        term = pred.escape(2)?;
+                        n += 1;
+                    }
+                    _ => return None,
+                },
+                _ => return None,
+            }
+        }
+    }
+
+    /// $n, s, z -> s n (n s z)
+    fn succ(&self) -> AST {
+        let body = apply(var("s"), [var("n"), apply(var("n"), [var("s"), var("z")])]);
+        lambda(&["n", "s", "z"], body)
+    }
+
+    /// $n -> n ($p, r -> p) zero
+    fn pred(&self) -> AST {
+        let body = apply(var("n"), [lambda(&["p", "r"], var("p")), self.numeral(0)]);
+        lambda(&["n"], body)
+    }
+
+    /// $n -> n ($p, r -> false) true
+    fn is_zero(&self) -> AST {
+        let body = apply(
+            var("n"),
+            [lambda(&["p", "r"], boolean(false)), boolean(true)],
+        );
+        lambda(&["n"], body)
+    }
+
+    /// $c, n -> n
+    fn nil(&self) -> AST {
+        lambda(&["c", "n"], var("n"))
+    }
+
+    /// $h, t, c, n -> c h t (t c n)
+    fn cons(&self) -> AST {
+        let body = apply(
+            var("c"),
+            [var("h"), var("t"), apply(var("t"), [var("c"), var("n")])],
+        );
+        lambda(&["h", "t", "c", "n"], body)
+    }
+
+    /// $c, n -> c x t (c y t' (... n)), where t is the tail and the recursion part t c n
+    /// is already reduced to the body of t
+    fn list(&self, items: Vec<AST>) -> AST {
+        items.iter().rev().fold(self.nil(), |tail, item| {
+            let head = item.to_de_bruijn().shift(2, 0);
+            let tail = tail.to_de_bruijn();
+            let recursion = tail.under_two().expect("Lists have two parameters").clone();
+            nameless_under_two(nameless_apply(DeBruijn::Bound(1), [head, tail, recursion]))
+        })
+    }
+
+    fn as_list(&self, ast: &AST) -> Option<Vec<AST>> {
+        let mut term = ast.to_de_bruijn();
+        let mut items = Vec::new();
+        loop {
+            match term.under_two()? {
+                DeBruijn::Bound(0) => return Some(items),
+                DeBruijn::Apply(f, _) => match f.as_ref() {
+                    DeBruijn::Apply(f, tail) => match f.as_ref() {
+                        DeBruijn::Apply(c, head) if **c == DeBruijn::Bound(1) => {
+                            items.push(head.escape(2)?.to_named(&VarGen::new()));
+                            term = tail.escape(2)?;
+                        }
+                        _ => return None,
+                    },
+                    _ => return None,
+                },
+                _ => return None,
+            }
+        }
+    }
+
+    /// $l -> l ($h, t, r -> h) nil, the head of the empty list is the empty list
+    fn head(&self) -> AST {
+        let body = apply(var("l"), [lambda(&["h", "t", "r"], var("h")), self.nil()]);
+        lambda(&["l"], body)
+    }
+
+    /// $l -> l ($h, t, r -> t) nil
+    fn tail(&self) -> AST {
+        let body = apply(var("l"), [lambda(&["h", "t", "r"], var("t")), self.nil()]);
+        lambda(&["l"], body)
+    }
+}
@@ -0,0 +1,106 @@
+use crate::ast::AST;
+use crate::debruijn::DeBruijn;
+use crate::encodings::build::{apply, lambda, nameless_apply, nameless_under_two, var};
+use crate::encodings::church::boolean;
+use crate::encodings::Encoding;
+use crate::variables::VarGen;
+
+/// Scott encoding, where a value is its own case analysis. A number is $s, z -> z for zero
+/// or $s, z -> s n for the successor of n, so the predecessor takes a single step
+pub struct Scott;
+
+impl Encoding for Scott {
+    fn name(&self) -> &'static str {
+        "scott"
+    }
+
+    /// $s, z -> s ($s, z -> s (... $s, z -> z))
+    fn numeral(&self, n: u64) -> AST {
+        (0..n).fold(lambda(&["s", "z"], var("z")), |pred, _| {
+            lambda(&["s", "z"], AST::apply(var("s"), pred))
+        })
+    }
+
+    fn as_numeral(&self, ast: &AST) -> Option<u64> {
+        let mut term = ast.to_de_bruijn();
+        let mut n = 0;
+        loop {
+            match term.under_two()? {
+                DeBruijn::Bound(0) => return Some(n),
+                DeBruijn::Apply(s, pred) if **s == DeBruijn::Bound(1) => {
+                    term = pred.escape(2)?;
+                    n += 1;
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    /// $n, s, z -> s n
+    fn succ(&self) -> AST {
+        lambda(&["n", "s", "z"], AST::apply(var("s"), var("n")))
+    }
+
+    /// $n -> n ($p -> p) zero
+    fn pred(&self) -> AST {
+        lambda(
+            &["n"],
+            apply(var("n"), [lambda(&["p"], var("p")), self.numeral(0)]),
+        )
+    }
+
+    /// $n -> n ($p -> false) true
+    fn is_zero(&self) -> AST {
+        let body = apply(var("n"), [lambda(&["p"], boolean(false)), boolean(true)]);
+        lambda(&["n"], body)
+    }
+
+    /// $c, n -> n
+    fn nil(&self) -> AST {
+        lambda(&["c", "n"], var("n"))
+    }
+
+    /// $h, t, c, n -> c h t
+    fn cons(&self) -> AST {
+        lambda(&["h", "t", "c", "n"], apply(var("c"), [var("h"), var("t")]))
+    }
+
+    /// $c, n -> c x ($c, n -> c y (... $c, n -> n))
+    fn list(&self, items: Vec<AST>) -> AST {
+        items.iter().rev().fold(self.nil(), |tail, item| {
+            let head = item.to_de_bruijn().shift(2, 0);
+            let tail = tail.to_de_bruijn().shift(2, 0);
+            nameless_under_two(nameless_apply(DeBruijn::Bound(1), [head, tail]))
+        })
+    }
+
+    fn as_list(&self, ast: &AST) -> Option<Vec<AST>> {
+        let mut term = ast.to_de_bruijn();
+        let mut items = Vec::new();
+        loop {
+            match term.under_two()? {
+                DeBruijn::Bound(0) => return Some(items),
+                DeBruijn::Apply(f, tail) => match f.as_ref() {
+                    DeBruijn::Apply(c, head) if **c == DeBruijn::Bound(1) => {
+                        items.push(head.escape(2)?.to_named(&VarGen::new()));
+                        term = tail.escape(2)?;
+                    }
+                    _ => return None,
+                },
+                _ => return None,
+            }
+        }
+    }
+
+    /// $l -> l ($h, t -> h) nil, the head of the empty list is the empty list
+    fn head(&self) -> AST {
+        let body = apply(var("l"), [lambda(&["h", "t"], var("h")), self.nil()]);
+        lambda(&["l"], body)
+    }
+
+    /// $l -> l ($h, t -> t) nil
+    fn tail(&self) -> AST {
+        let body = apply(var("l"), [lambda(&["h", "t"], var("t")), self.nil()]);
+        lambda(&["l"], body)
+    }
+}
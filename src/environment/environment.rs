@@ -0,0 +1,67 @@
+use crate::alpha::AlphaVariant;
+use crate::ast::Term;
+use crate::ast::AST;
+use crate::encodings::PRELUDE;
+use crate::parser::Statement;
+use crate::substitution::Substitution;
+
+/// Type represents the definitions made so far, in the order they were made.
+/// Each definition is stored with the earlier definitions already substituted into it
+#[derive(Debug, Clone, Default)]
+pub struct Environment {
+    definitions: Vec<(String, AST)>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Environment {
+            definitions: Vec::new(),
+        }
+    }
+
+    /// Create an environment with the definitions of the prelude
+    pub fn prelude() -> Self {
+        Statement::eval_program(PRELUDE)
+            .expect("The prelude is a valid program")
+            .into_iter()
+            .fold(Environment::new(), |env, statement| match statement {
+                Statement::Definition(name, ast) => env.with(name, ast),
+                Statement::Expression(_) => env,
+            })
+    }
+
+    /// Define a name and return a new copy. A later definition of the same name replaces
+    /// the earlier one, but terms defined in between keep the value they were defined with.
+    /// The positions are dropped, as they refer to the input the definition was read from
+    pub fn with(self, name: String, term: AST) -> Self {
+        let term = self.resolve(term).without_spans();
+        let mut definitions: Vec<(String, AST)> = self
+            .definitions
+            .into_iter()
+            .filter(|(defined, _)| *defined != name)
+            .collect();
+        definitions.push((name, term));
+        Environment { definitions }
+    }
+
+    /// Iterate over the definitions in the order they were made
+    pub fn definitions(&self) -> impl Iterator<Item = &(String, AST)> {
+        self.definitions.iter()
+    }
+
+    /// Substitute every defined name that is free in the term with its definition
+    pub fn resolve(&self, ast: AST) -> AST {
+        self.definitions.iter().fold(ast, |ast, (name, term)| {
+            let var = Term::Var(name.clone());
+            match ast.free_vars.contains(&var) {
+                false => ast,
+                // We may need to first create an alpha variant if some of the free variables
+                // of the definition appear as binding in the term
+                true => ast
+                    .clone()
+                    .substitute(var.clone(), term.clone())
+                    .unwrap_or_else(|_| ast.alpha_variant().substitute(var, term.clone()).unwrap()),
+            }
+        })
+    }
+}
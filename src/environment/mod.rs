@@ -0,0 +1,4 @@
+//! This module keeps track of named definitions and substitutes them into terms
+pub mod environment;
+
+pub use self::environment::Environment;
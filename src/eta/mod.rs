@@ -0,0 +1,4 @@
+//! This module handles the eta reduction and expansion of terms
+pub mod reduction;
+
+pub use self::reduction::EtaReduction;
@@ -0,0 +1,129 @@
+use crate::ast::Term;
+use crate::ast::AST;
+use crate::beta::{BetaReduction, EvalOrder, ReductionLimits, ReductionResult};
+
+pub trait EtaReduction {
+    fn eta_reduce(self) -> AST;
+    fn eta_expand(self) -> AST;
+    fn beta_eta_reduce(
+        self,
+        order: Option<&EvalOrder>,
+        limits: &ReductionLimits,
+    ) -> ReductionResult;
+    fn beta_eta_equivalent(&self, other: &AST, limits: &ReductionLimits) -> Option<bool>;
+}
+
+impl AST {
+    /// If the term is an eta redex, i.e. $x -> f x where x is not free in f, return f
+    fn eta_contract(&self) -> Option<AST> {
+        match &self.term {
+            Term::Abstr(param, body) => match &body.term {
+                Term::Apply(f, arg) if arg.term == param.term => {
+                    match f.free_vars.contains(&param.term) {
+                        true => None,
+                        false => Some((**f).clone()),
+                    }
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl EtaReduction for AST {
+    /// Reduce all eta redexes. The subterms are reduced first, so that contracting the term
+    /// itself cannot leave any eta redex behind
+    fn eta_reduce(self) -> AST {
+        let reduced = match self.term {
+            Term::Var(_) => self,
+            Term::Abstr(param, body) => AST::abstr(*param, body.eta_reduce()),
+            Term::Apply(f, arg) => AST::apply(f.eta_reduce(), arg.eta_reduce()),
+        };
+        reduced.eta_contract().unwrap_or(reduced)
+    }
+
+    /// Wrap the term in an abstraction that applies it to a fresh variable, i.e. f => $x -> f x
+    fn eta_expand(self) -> AST {
+        let var = AST::fresh(self.all_vars());
+        AST::abstr(var.clone(), AST::apply(self, var))
+    }
+
+    /// Reduce to the beta normal form within the limits and then remove the eta redexes.
+    /// Eta reduction of a term in beta normal form cannot create new beta redexes
+    fn beta_eta_reduce(
+        self,
+        order: Option<&EvalOrder>,
+        limits: &ReductionLimits,
+    ) -> ReductionResult {
+        match self.reduce_bounded(order, limits) {
+            ReductionResult::Normalized(ast) => ReductionResult::Normalized(ast.eta_reduce()),
+            result => result,
+        }
+    }
+
+    /// Check whether the two terms are equal up to alpha, beta and eta conversion by comparing
+    /// their beta-eta normal forms. Returns None if one of them is not normalized within the limits
+    fn beta_eta_equivalent(&self, other: &AST, limits: &ReductionLimits) -> Option<bool> {
+        let normal_form = |ast: &AST| match ast.clone().beta_eta_reduce(None, limits) {
+            ReductionResult::Normalized(ast) => Some(ast),
+            _ => None,
+        };
+        Some(normal_form(self)? == normal_form(other)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EtaReduction;
+    use crate::ast::AST;
+    use crate::beta::{ReductionLimits, ReductionResult};
+
+    fn parse(input: &str) -> AST {
+        AST::eval(input).unwrap()
+    }
+
+    fn limits() -> ReductionLimits {
+        ReductionLimits {
+            fuel: Some(1000),
+            deadline: None,
+        }
+    }
+
+    #[test]
+    fn eta_reduce() {
+        assert_eq!(parse("$x -> f x").eta_reduce(), parse("f"));
+        assert_eq!(parse("$x, y -> f x y").eta_reduce(), parse("f"));
+        // The variable is free in the function, so it is not a redex
+        assert_eq!(parse("$x -> x x").eta_reduce(), parse("$x -> x x"));
+    }
+
+    #[test]
+    fn eta_expand() {
+        let expanded = parse("f").eta_expand();
+        assert_eq!(expanded, parse("$x -> f x"));
+        assert_eq!(expanded.eta_reduce(), parse("f"));
+        // The new parameter does not capture the free variables of the term
+        assert_eq!(parse("x").eta_expand(), parse("$y -> x y"));
+    }
+
+    #[test]
+    fn beta_eta_reduce() {
+        match parse("($f, x -> f x) g").beta_eta_reduce(None, &limits()) {
+            ReductionResult::Normalized(ast) => assert_eq!(ast, parse("g")),
+            result => panic!("Expected a normal form, found {}", result),
+        }
+    }
+
+    #[test]
+    fn beta_eta_equivalent() {
+        let equivalent =
+            |left: &str, right: &str| parse(left).beta_eta_equivalent(&parse(right), &limits());
+        assert_eq!(equivalent("$x -> f x", "f"), Some(true));
+        assert_eq!(equivalent("($x -> x) f", "$y -> f y"), Some(true));
+        assert_eq!(equivalent("$x, y -> x", "$a -> ($b, c -> b) a"), Some(true));
+        assert_eq!(equivalent("$x, y -> x", "$x, y -> y"), Some(false));
+        // The first term has no normal form, so there is no answer
+        assert_eq!(equivalent("($x -> x x) ($x -> x x)", "a"), None);
+    }
+}
@@ -0,0 +1,214 @@
+use crate::ast::AST;
+use crate::beta::{ReductionLimits, ReductionResult};
+use crate::debruijn::DeBruijn;
+use crate::variables::VarGen;
+use std::rc::Rc;
+use std::time::Instant;
+
+/// Type represents a term together with the environment that binds its indices
+#[derive(Clone)]
+pub struct Closure {
+    term: Rc<DeBruijn>,
+    env: Env,
+}
+
+/// Type represents an environment, i.e. the closures bound to de Bruijn indices.
+/// The innermost binding is the head of the list, so index 0 is the first node
+#[derive(Clone, Default)]
+pub struct Env {
+    head: Option<Rc<EnvNode>>,
+}
+
+struct EnvNode {
+    closure: Closure,
+    tail: Env,
+}
+
+impl Env {
+    pub fn new() -> Self {
+        Env { head: None }
+    }
+
+    /// Return a new environment with the closure bound to index 0
+    pub fn with(&self, closure: Closure) -> Self {
+        Env {
+            head: Some(Rc::new(EnvNode {
+                closure,
+                tail: self.clone(),
+            })),
+        }
+    }
+
+    /// Look up the closure bound to the index
+    pub fn get(&self, index: usize) -> &Closure {
+        let mut env = self;
+        for _ in 0..index {
+            env = &env.head.as_ref().expect("Unbound de Bruijn index").tail;
+        }
+        &env.head.as_ref().expect("Unbound de Bruijn index").closure
+    }
+}
+
+impl Closure {
+    pub fn new(term: Rc<DeBruijn>, env: Env) -> Self {
+        Closure { term, env }
+    }
+
+    /// Substitute the environment back into the term
+    pub fn unload(&self) -> DeBruijn {
+        Closure::unload_in(&self.term, &self.env, 0)
+    }
+
+    /// Unloading under the given number of binders of the term itself
+    fn unload_in(term: &DeBruijn, env: &Env, depth: usize) -> DeBruijn {
+        match term {
+            DeBruijn::Bound(index) if *index < depth => DeBruijn::Bound(*index),
+            // The closure is unloaded on its own and then moved under the binders
+            DeBruijn::Bound(index) => env.get(index - depth).unload().shift(depth as isize, 0),
+            DeBruijn::Free(s) => DeBruijn::Free(s.clone()),
+            DeBruijn::Abstr(body) => {
+                DeBruijn::Abstr(Rc::new(Closure::unload_in(body, env, depth + 1)))
+            }
+            DeBruijn::Apply(f, arg) => DeBruijn::Apply(
+                Rc::new(Closure::unload_in(f, env, depth)),
+                Rc::new(Closure::unload_in(arg, env, depth)),
+            ),
+        }
+    }
+}
+
+/// Type represents the state of a Krivine machine
+///  - *closure*: the term being evaluated in its environment
+///  - *stack*: the arguments waiting for an abstraction, the next one on top
+///  - *steps*: the number of beta reductions performed so far
+pub struct Krivine {
+    closure: Closure,
+    stack: Vec<Closure>,
+    steps: usize,
+}
+
+impl Krivine {
+    /// Load a term into the machine
+    pub fn new(ast: &AST) -> Self {
+        Krivine {
+            closure: Closure::new(Rc::new(ast.to_de_bruijn()), Env::new()),
+            stack: Vec::new(),
+            steps: 0,
+        }
+    }
+
+    /// Run the machine up to the next beta reduction. Returns false when the machine stops,
+    /// i.e. the term is an abstraction with no arguments or a free variable at the head
+    pub fn step(&mut self) -> bool {
+        loop {
+            let Closure { term, env } = self.closure.clone();
+            match &*term {
+                // Push the argument, unevaluated, and continue with the function.
+                // A variable is pushed as the closure it is bound to, otherwise every pass of
+                // a loop would add one more closure to look through
+                DeBruijn::Apply(f, arg) => {
+                    let arg = match arg.as_ref() {
+                        DeBruijn::Bound(index) => env.get(*index).clone(),
+                        _ => Closure::new(arg.clone(), env.clone()),
+                    };
+                    self.stack.push(arg);
+                    self.closure = Closure::new(f.clone(), env.clone());
+                }
+                // Continue with the closure the variable is bound to
+                DeBruijn::Bound(index) => self.closure = env.get(*index).clone(),
+                // Bind the argument on top of the stack and enter the body
+                DeBruijn::Abstr(body) => {
+                    return match self.stack.pop() {
+                        Some(arg) => {
+                            self.closure = Closure::new(body.clone(), env.with(arg));
+                            self.steps += 1;
+                            true
+                        }
+                        None => false,
+                    }
+                }
+                DeBruijn::Free(_) => return false,
+            }
+        }
+    }
+
+    /// Read the current state back into a term, the closure applied to the arguments on the stack
+    pub fn term(&self) -> AST {
+        self.stack
+            .iter()
+            .rev()
+            .fold(self.closure.unload(), |term, arg| {
+                DeBruijn::Apply(Rc::new(term), Rc::new(arg.unload()))
+            })
+            .to_named(&VarGen::new())
+    }
+
+    /// Evaluate a term to its weak head normal form or until a limit is hit
+    pub fn run(ast: &AST, limits: &ReductionLimits) -> ReductionResult {
+        let mut machine = Krivine::new(ast);
+        loop {
+            if limits.fuel.is_some_and(|fuel| machine.steps >= fuel) {
+                return ReductionResult::OutOfFuel(machine.term(), machine.steps);
+            }
+            if limits
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+            {
+                return ReductionResult::TimedOut(machine.term());
+            }
+            if !machine.step() {
+                return ReductionResult::weak_head(machine.term());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Krivine;
+    use crate::ast::AST;
+    use crate::beta::{ReductionLimits, ReductionResult};
+
+    fn run(input: &str) -> ReductionResult {
+        let limits = ReductionLimits {
+            fuel: Some(1000),
+            deadline: None,
+        };
+        Krivine::run(&AST::eval(input).unwrap(), &limits)
+    }
+
+    #[test]
+    fn normal_form() {
+        match run("($x, y -> y x) a ($z -> z)") {
+            ReductionResult::Normalized(ast) => assert_eq!(ast, AST::eval("a").unwrap()),
+            result => panic!("Expected a normal form, found {}", result),
+        }
+    }
+
+    #[test]
+    fn weak_head_is_not_normalized() {
+        // The machine does not enter the abstraction, so the redex in its body is left
+        match run("($x -> x) ($y -> ($z -> z) y)") {
+            ReductionResult::WeakHead(ast) => {
+                assert_eq!(ast, AST::eval("$y -> ($z -> z) y").unwrap())
+            }
+            result => panic!("Expected a weak head normal form, found {}", result),
+        }
+    }
+
+    #[test]
+    fn diverging_term_runs_out_of_fuel() {
+        let limits = ReductionLimits {
+            fuel: Some(100_000),
+            deadline: None,
+        };
+        let omega = AST::eval("($x -> x x) ($x -> x x)").unwrap();
+        match Krivine::run(&omega, &limits) {
+            ReductionResult::OutOfFuel(ast, steps) => {
+                assert_eq!(ast, omega);
+                assert_eq!(steps, 100_000);
+            }
+            result => panic!("Expected to run out of fuel, found {}", result),
+        }
+    }
+}
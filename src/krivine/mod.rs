@@ -0,0 +1,5 @@
+//! This module implements the Krivine abstract machine, which evaluates terms by call-by-name
+//! to weak head normal form without ever substituting into them
+pub mod machine;
+
+pub use self::machine::Krivine;
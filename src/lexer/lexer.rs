@@ -1,31 +1,253 @@
-use regex::Regex;
+use crate::span::{render_snippet, Span};
+use std::fmt;
+use std::iter::Peekable;
+use std::str::CharIndices;
 
-/// Type that represents valid tokens
-#[derive(Debug)]
-pub enum Token {
+/// Type that represents valid kinds of tokens
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
     Var(String),
     Lambda,
     Arrow,
     LParen,
     RParen,
     Comma,
+    Colon,
+    Dot,
+    LBracket,
+    RBracket,
+    TypeLambda,
+    Forall,
+    And,
+    Or,
+    Bottom,
+    Equals,
+    Let,
+    In,
+    Separator,
+    Eof,
+}
+
+/// Type that represents a token together with its position in the input
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+/// Type for errors during tokenization
+#[derive(Debug, Clone)]
+pub enum LexError {
+    UnexpectedChar { ch: char, span: Span },
+}
+
+impl LexError {
+    /// Render the error as an annotated snippet of the input it was produced from
+    pub fn render(&self, source: &str) -> String {
+        match self {
+            LexError::UnexpectedChar { ch, span } => {
+                render_snippet(source, *span, &format!("Unexpected character {:?}", ch))
+            }
+        }
+    }
+}
+
+/// Type that reads the input one character at a time, keeping track of the position
+struct Lexer<'a> {
+    input: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    line: usize,
+    column: usize,
+    started: bool,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str) -> Self {
+        Lexer {
+            input,
+            chars: input.char_indices().peekable(),
+            line: 1,
+            column: 1,
+            started: false,
+        }
+    }
+
+    /// Offset of the next character, or the end of the input
+    fn offset(&mut self) -> usize {
+        self.chars.peek().map_or(self.input.len(), |(i, _)| *i)
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|(_, c)| *c)
+    }
+
+    /// Consume the next character and move the position past it
+    fn bump(&mut self) -> Option<char> {
+        let (_, c) = self.chars.next()?;
+        match c {
+            '\n' => {
+                self.line += 1;
+                self.column = 1;
+            }
+            _ => self.column += 1,
+        }
+        Some(c)
+    }
+
+    /// Consume characters up to the end of the line
+    fn skip_line(&mut self) {
+        while self.peek().is_some_and(|c| c != '\n') {
+            self.bump();
+        }
+    }
+
+    /// Skip whitespace and comments starting with '#' or '--' in front of the next token.
+    /// Returns whether a line break was crossed
+    fn skip_trivia(&mut self) -> bool {
+        let mut newline = false;
+        loop {
+            match self.peek() {
+                Some('\n') => {
+                    newline = true;
+                    self.bump();
+                }
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                }
+                Some('#') => self.skip_line(),
+                Some('-') if self.input[self.offset()..].starts_with("--") => self.skip_line(),
+                _ => return newline,
+            }
+        }
+    }
+
+    /// Read the next token, skipping any whitespace in front of it
+    fn next_token(&mut self) -> Result<Token, LexError> {
+        let newline = self.skip_trivia();
+        let (start, line, column) = (self.offset(), self.line, self.column);
+        // A line starting in the first column begins a new statement,
+        // so continuation lines of a statement have to be indented
+        if newline && column == 1 && self.started && self.peek().is_some() {
+            let span = Span::new(start, start, line, column);
+            return Ok(Token {
+                kind: TokenKind::Separator,
+                span,
+            });
+        }
+        self.started = true;
+        let kind = match self.bump() {
+            None => TokenKind::Eof,
+            Some(';') => TokenKind::Separator,
+            Some('$') => TokenKind::Lambda,
+            Some('(') => TokenKind::LParen,
+            Some(')') => TokenKind::RParen,
+            Some(',') => TokenKind::Comma,
+            Some(':') => TokenKind::Colon,
+            Some('.') => TokenKind::Dot,
+            Some('[') => TokenKind::LBracket,
+            Some(']') => TokenKind::RBracket,
+            // Connectives of propositions
+            Some('&') => TokenKind::And,
+            Some('|') => TokenKind::Or,
+            Some('⊥') => TokenKind::Bottom,
+            // The type lambda and the universal quantifier of System F
+            Some('/') if self.peek() == Some('\\') => {
+                self.bump();
+                TokenKind::TypeLambda
+            }
+            Some('\\') if self.peek() == Some('/') => {
+                self.bump();
+                TokenKind::Forall
+            }
+            Some('=') => TokenKind::Equals,
+            Some('-') if self.peek() == Some('>') => {
+                self.bump();
+                TokenKind::Arrow
+            }
+            // Variable names start with a letter or underscore and may contain digits
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+                {
+                    self.bump();
+                }
+                // Keywords cannot be used as variable names
+                match &self.input[start..self.offset()] {
+                    "let" => TokenKind::Let,
+                    "in" => TokenKind::In,
+                    name => TokenKind::Var(name.to_string()),
+                }
+            }
+            Some(ch) => {
+                let span = Span::new(start, self.offset(), line, column);
+                return Err(LexError::UnexpectedChar { ch, span });
+            }
+        };
+        let span = Span::new(start, self.offset(), line, column);
+        Ok(Token { kind, span })
+    }
 }
 
 /// Function that converts input string into a sequence of tokens
-pub fn tokenize(input: &str) -> Result<Vec<Token>, regex::Error> {
-    let re = Regex::new(r"\$|->|[a-zA-Z_][a-zA-Z_0-9]*|[(),]")?;
-    Ok(re
-        .find_iter(input)
-        .filter_map(|mat| match mat.as_str() {
-            "$" => Some(Token::Lambda),
-            "->" => Some(Token::Arrow),
-            "(" => Some(Token::LParen),
-            ")" => Some(Token::RParen),
-            "," => Some(Token::Comma),
-            var if var.chars().all(|c| c.is_alphanumeric() || c == '_') => {
-                Some(Token::Var(var.to_string()))
-            }
-            _ => None,
-        })
-        .collect())
+/// The sequence is always terminated by an *Eof* token
+pub fn tokenize(input: &str) -> Result<Vec<Token>, LexError> {
+    let mut lexer = Lexer::new(input);
+    let mut tokens = Vec::new();
+    loop {
+        let token = lexer.next_token()?;
+        let is_eof = token.kind == TokenKind::Eof;
+        tokens.push(token);
+        if is_eof {
+            return Ok(tokens);
+        }
+    }
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenKind::Var(s) => write!(f, "'{}'", s),
+            TokenKind::Lambda => write!(f, "'$'"),
+            TokenKind::Arrow => write!(f, "'->'"),
+            TokenKind::LParen => write!(f, "'('"),
+            TokenKind::RParen => write!(f, "')'"),
+            TokenKind::Comma => write!(f, "','"),
+            TokenKind::Colon => write!(f, "':'"),
+            TokenKind::Dot => write!(f, "'.'"),
+            TokenKind::LBracket => write!(f, "'['"),
+            TokenKind::RBracket => write!(f, "']'"),
+            TokenKind::TypeLambda => write!(f, "'/\\'"),
+            TokenKind::Forall => write!(f, "'\\/'"),
+            TokenKind::And => write!(f, "'&'"),
+            TokenKind::Or => write!(f, "'|'"),
+            TokenKind::Bottom => write!(f, "'⊥'"),
+            TokenKind::Equals => write!(f, "'='"),
+            TokenKind::Let => write!(f, "'let'"),
+            TokenKind::In => write!(f, "'in'"),
+            TokenKind::Separator => write!(f, "end of statement"),
+            TokenKind::Eof => write!(f, "end of input"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lexer::{tokenize, LexError};
+    use crate::span::Span;
+
+    /// The character and the span of the error of tokenizing the input
+    fn unexpected(input: &str) -> (char, Span) {
+        match tokenize(input) {
+            Err(LexError::UnexpectedChar { ch, span }) => (ch, span),
+            Ok(tokens) => panic!("Expected an error, found {:?}", tokens),
+        }
+    }
+
+    #[test]
+    fn unexpected_char() {
+        assert_eq!(unexpected("$x -> x + 1"), ('+', Span::new(8, 9, 1, 9)));
+        // The position is counted from the start of the line it is on
+        assert_eq!(unexpected("$x ->\n  x ?"), ('?', Span::new(10, 11, 2, 5)));
+    }
 }
@@ -1,3 +1,3 @@
 //! Lexer modules handles the tokenization of the input text
 pub mod lexer;
-pub use self::lexer::{tokenize, Token};
+pub use self::lexer::{tokenize, LexError, Token, TokenKind};
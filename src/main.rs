@@ -1,22 +1,65 @@
 mod alpha;
 mod ast;
+mod backend;
 mod beta;
+mod cek;
+mod cli;
+mod debruijn;
+mod encodings;
+mod environment;
+mod eta;
+mod krivine;
 mod lexer;
+mod nbe;
+mod need;
 mod parser;
+mod printer;
+mod proof;
+mod repl;
+mod ski;
+mod span;
 mod substitution;
+mod systemf;
+mod types;
 mod variables;
-use std::fmt;
+mod vm;
 
-use ast::ast::AST;
-use beta::{BetaReduction, EvalOrder};
+use cli::cli::USAGE;
+use cli::Cli;
+use std::process::ExitCode;
+use std::thread;
 
-fn main() {
-    print_tree("($x, y -> x y) ($c, d -> c d) a");
-    print_tree("a b c");
+/// Size of the stack of the thread that runs the program. The reductions recurse over the
+/// terms, and the bounded ones stop before a term gets deeper than this stack can hold
+const STACK_SIZE: usize = 1 << 30;
+
+fn main() -> ExitCode {
+    thread::Builder::new()
+        .name("main".to_string())
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .expect("Failed to start the main thread")
+        .join()
+        .unwrap_or(ExitCode::FAILURE)
+}
+
+/// Run a test on a stack as large as the one of the program, the deep terms need it
+#[cfg(test)]
+fn with_large_stack(test: impl FnOnce() + Send + 'static) {
+    thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(test)
+        .unwrap()
+        .join()
+        .unwrap();
 }
 
-fn print_tree(input: &str) {
-    let tokens = lexer::tokenize(input).unwrap();
-    let tree = parser::parse(&tokens);
-    println!("{:?}", tree)
+fn run() -> ExitCode {
+    match Cli::parse(std::env::args().skip(1)) {
+        Ok(cli) => cli.execute(),
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            ExitCode::from(2)
+        }
+    }
 }
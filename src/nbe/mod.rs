@@ -0,0 +1,5 @@
+//! This module implements normalization by evaluation. Terms are interpreted as Rust closures
+//! and the closures are quoted back into terms in normal form
+pub mod nbe;
+
+pub use self::nbe::normalize;
@@ -0,0 +1,299 @@
+use crate::ast::AST;
+use crate::beta::{ReductionLimits, ReductionResult};
+use crate::debruijn::DeBruijn;
+use crate::variables::VarGen;
+use std::cell::{Cell, OnceCell};
+use std::rc::Rc;
+use std::time::Instant;
+
+/// Maximum nesting of evaluations and quotations. Evaluation recurses as deep as the
+/// computation goes, this keeps it within the stack the program runs on
+const MAX_DEPTH: usize = 1 << 19;
+
+/// Type represents the meaning of a term
+///  - *Function*: an abstraction, interpreted as a function from arguments to meanings
+///  - *Neutral*: a term stuck on a variable, which no argument can reduce
+#[derive(Clone)]
+pub enum Semantic {
+    Function(Rc<dyn Fn(Rc<Lazy>) -> Result<Semantic, Stop>>),
+    Neutral(Rc<Neutral>),
+}
+
+/// Type represents an argument, whose meaning is computed the first time it is needed.
+/// Arguments a function does not use are never evaluated, as in the normal order
+pub struct Lazy {
+    suspended: Option<(Rc<DeBruijn>, Env)>,
+    value: OnceCell<Semantic>,
+}
+
+/// Type represents a term stuck on a variable
+///  - *Free*: a free variable of the term
+///  - *Level*: the variable of an abstraction being quoted, counting binders from the outside
+///  - *Apply*: a stuck term applied to an argument
+pub enum Neutral {
+    Free(String),
+    Level(usize),
+    Apply(Rc<Neutral>, Rc<Lazy>),
+}
+
+/// Type represents the reason the evaluation stopped before the normal form
+///  - *OutOfFuel*: the evaluation performed the maximum number of beta reductions
+///  - *TimedOut*: the deadline passed
+///  - *TooDeep*: the evaluations nested deeper than the stack allows
+#[derive(Debug, Clone, Copy)]
+pub enum Stop {
+    OutOfFuel,
+    TimedOut,
+    TooDeep,
+}
+
+/// Type represents an environment, i.e. the arguments bound to de Bruijn indices.
+/// The innermost binding is the head of the list, so index 0 is the first node
+#[derive(Clone, Default)]
+pub struct Env {
+    head: Option<Rc<EnvNode>>,
+}
+
+struct EnvNode {
+    value: Rc<Lazy>,
+    tail: Env,
+}
+
+impl Env {
+    pub fn new() -> Self {
+        Env { head: None }
+    }
+
+    /// Return a new environment with the argument bound to index 0
+    pub fn with(&self, value: Rc<Lazy>) -> Self {
+        Env {
+            head: Some(Rc::new(EnvNode {
+                value,
+                tail: self.clone(),
+            })),
+        }
+    }
+
+    /// Look up the argument bound to the index
+    pub fn get(&self, index: usize) -> &Rc<Lazy> {
+        let mut env = self;
+        for _ in 0..index {
+            env = &env.head.as_ref().expect("Unbound de Bruijn index").tail;
+        }
+        &env.head.as_ref().expect("Unbound de Bruijn index").value
+    }
+}
+
+impl Lazy {
+    /// An argument that waits for its evaluation
+    fn suspended(term: Rc<DeBruijn>, env: Env) -> Self {
+        Lazy {
+            suspended: Some((term, env)),
+            value: OnceCell::new(),
+        }
+    }
+
+    /// An argument with a known meaning
+    fn evaluated(value: Semantic) -> Self {
+        Lazy {
+            suspended: None,
+            value: OnceCell::from(value),
+        }
+    }
+
+    /// The meaning of the argument, evaluated on the first demand and then reused
+    fn force(&self, budget: &Rc<Budget>) -> Result<Semantic, Stop> {
+        if let Some(value) = self.value.get() {
+            return Ok(value.clone());
+        }
+        let (term, env) = self
+            .suspended
+            .as_ref()
+            .expect("An argument is either suspended or evaluated");
+        let value = eval(term, env, budget)?;
+        Ok(self.value.get_or_init(|| value).clone())
+    }
+}
+
+/// Type counts the beta reductions and the nesting of the recursion, shared by all functions
+/// of one normalization
+struct Budget {
+    limits: ReductionLimits,
+    steps: Cell<usize>,
+    depth: Cell<usize>,
+}
+
+impl Budget {
+    /// Count one more beta reduction, unless a limit was hit
+    fn spend(&self) -> Result<(), Stop> {
+        if self
+            .limits
+            .fuel
+            .is_some_and(|fuel| self.steps.get() >= fuel)
+        {
+            return Err(Stop::OutOfFuel);
+        }
+        if self
+            .limits
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(Stop::TimedOut);
+        }
+        self.steps.set(self.steps.get() + 1);
+        Ok(())
+    }
+
+    /// Run one more level of the recursion, unless it would be nested too deep
+    fn nested<T>(&self, f: impl FnOnce() -> Result<T, Stop>) -> Result<T, Stop> {
+        if self.depth.get() >= MAX_DEPTH {
+            return Err(Stop::TooDeep);
+        }
+        self.depth.set(self.depth.get() + 1);
+        let result = f();
+        self.depth.set(self.depth.get() - 1);
+        result
+    }
+}
+
+/// Interpret the term in the environment
+fn eval(term: &DeBruijn, env: &Env, budget: &Rc<Budget>) -> Result<Semantic, Stop> {
+    budget.nested(|| eval_nested(term, env, budget))
+}
+
+fn eval_nested(term: &DeBruijn, env: &Env, budget: &Rc<Budget>) -> Result<Semantic, Stop> {
+    match term {
+        DeBruijn::Bound(index) => env.get(*index).force(budget),
+        DeBruijn::Free(s) => Ok(Semantic::Neutral(Rc::new(Neutral::Free(s.clone())))),
+        // The body is evaluated every time the function is called, with the argument bound
+        DeBruijn::Abstr(body) => {
+            let (body, env, budget) = (body.clone(), env.clone(), budget.clone());
+            Ok(Semantic::Function(Rc::new(move |arg| {
+                eval(&body, &env.with(arg), &budget)
+            })))
+        }
+        DeBruijn::Apply(f, arg) => {
+            let f = eval(f, env, budget)?;
+            apply(
+                f,
+                Rc::new(Lazy::suspended(arg.clone(), env.clone())),
+                budget,
+            )
+        }
+    }
+}
+
+/// Apply a meaning to an argument. Only a function call is a beta reduction
+fn apply(f: Semantic, arg: Rc<Lazy>, budget: &Budget) -> Result<Semantic, Stop> {
+    match f {
+        Semantic::Function(f) => {
+            budget.spend()?;
+            f(arg)
+        }
+        Semantic::Neutral(neutral) => Ok(Semantic::Neutral(Rc::new(Neutral::Apply(neutral, arg)))),
+    }
+}
+
+/// Turn a meaning back into a term under the given number of binders
+fn quote(value: &Semantic, depth: usize, budget: &Rc<Budget>) -> Result<DeBruijn, Stop> {
+    budget.nested(|| quote_nested(value, depth, budget))
+}
+
+fn quote_nested(value: &Semantic, depth: usize, budget: &Rc<Budget>) -> Result<DeBruijn, Stop> {
+    match value {
+        // Call the function with a variable that stands for its parameter
+        Semantic::Function(f) => {
+            let var = Semantic::Neutral(Rc::new(Neutral::Level(depth)));
+            let body = f(Rc::new(Lazy::evaluated(var)))?;
+            Ok(DeBruijn::Abstr(Rc::new(quote(&body, depth + 1, budget)?)))
+        }
+        Semantic::Neutral(neutral) => quote_neutral(neutral, depth, budget),
+    }
+}
+
+fn quote_neutral(neutral: &Neutral, depth: usize, budget: &Rc<Budget>) -> Result<DeBruijn, Stop> {
+    match neutral {
+        Neutral::Free(s) => Ok(DeBruijn::Free(s.clone())),
+        Neutral::Level(level) => Ok(DeBruijn::Bound(depth - 1 - level)),
+        // Arguments of a stuck term are part of the normal form, so they are needed now
+        Neutral::Apply(f, arg) => budget.nested(|| {
+            Ok(DeBruijn::Apply(
+                Rc::new(quote_neutral(f, depth, budget)?),
+                Rc::new(quote(&arg.force(budget)?, depth, budget)?),
+            ))
+        }),
+    }
+}
+
+/// Normalize a term by evaluation within the limits. The evaluation has no intermediate
+/// terms, so a stopped normalization carries the original term
+pub fn normalize(ast: &AST, limits: &ReductionLimits) -> ReductionResult {
+    let budget = Rc::new(Budget {
+        limits: *limits,
+        steps: Cell::new(0),
+        depth: Cell::new(0),
+    });
+    let normal =
+        eval(&ast.to_de_bruijn(), &Env::new(), &budget).and_then(|value| quote(&value, 0, &budget));
+    match normal {
+        Ok(normal) => ReductionResult::Normalized(normal.to_named(&VarGen::new())),
+        Err(Stop::OutOfFuel) => ReductionResult::OutOfFuel(ast.clone(), budget.steps.get()),
+        Err(Stop::TimedOut) => ReductionResult::TimedOut(ast.clone()),
+        Err(Stop::TooDeep) => ReductionResult::Aborted(
+            ast.clone(),
+            "The evaluation nested deeper than the stack allows".to_string(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize;
+    use crate::ast::AST;
+    use crate::beta::{ReductionLimits, ReductionResult};
+    use crate::encodings::build::{apply, lambda, var};
+    use crate::encodings::church;
+    use crate::with_large_stack;
+
+    fn unlimited() -> ReductionLimits {
+        ReductionLimits::new()
+    }
+
+    #[test]
+    fn normal_form() {
+        let ast = AST::eval("$x -> ($y, z -> z y) x ($w -> w)").unwrap();
+        match normalize(&ast, &unlimited()) {
+            ReductionResult::Normalized(ast) => assert_eq!(ast, AST::eval("$x -> x").unwrap()),
+            result => panic!("Expected a normal form, found {}", result),
+        }
+    }
+
+    #[test]
+    fn out_of_fuel() {
+        let limits = ReductionLimits {
+            fuel: Some(100),
+            deadline: None,
+        };
+        let omega = AST::eval("($x -> x x) ($x -> x x)").unwrap();
+        match normalize(&omega, &limits) {
+            ReductionResult::OutOfFuel(ast, steps) => {
+                assert_eq!(ast, omega);
+                assert_eq!(steps, 100);
+            }
+            result => panic!("Expected to run out of fuel, found {}", result),
+        }
+    }
+
+    #[test]
+    fn deep_evaluation_is_aborted() {
+        with_large_stack(|| {
+            // 2^20 applications of the identity, each of them nests the evaluation further
+            let power = apply(church::pow(), [church::numeral(2), church::numeral(20)]);
+            let ast = apply(power, [lambda(&["x"], var("x")), var("a")]);
+            match normalize(&ast, &unlimited()) {
+                ReductionResult::Aborted(aborted, _) => assert_eq!(aborted, ast),
+                result => panic!("Expected the normalization to be aborted, found {}", result),
+            }
+        });
+    }
+}
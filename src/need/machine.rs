@@ -0,0 +1,297 @@
+use crate::ast::AST;
+use crate::beta::{ReductionLimits, ReductionResult};
+use crate::debruijn::DeBruijn;
+use crate::need::thunk::{Head, ThunkState};
+use crate::need::{Env, Thunk, Value};
+use crate::variables::VarGen;
+use std::fmt;
+use std::rc::Rc;
+use std::time::Instant;
+
+/// Type for errors during call-by-need evaluation
+///  - *OutOfFuel*: the evaluation performed the maximum number of beta reductions
+///  - *TimedOut*: the deadline passed
+#[derive(Debug, Clone)]
+pub enum NeedError {
+    OutOfFuel(usize),
+    TimedOut,
+}
+
+impl fmt::Display for NeedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NeedError::OutOfFuel(steps) => write!(f, "Stopped after {} steps", steps),
+            NeedError::TimedOut => write!(f, "Timed out"),
+        }
+    }
+}
+
+/// Type represents the counters of a single argument
+///  - *argument*: the term that was passed as the argument
+///  - *forced*: how many times its value was demanded
+///  - *evaluated*: how many times its value was computed, which is never more than once
+#[derive(Debug, Clone)]
+pub struct ThunkReport {
+    pub argument: AST,
+    pub forced: usize,
+    pub evaluated: usize,
+}
+
+/// Type represents a call-by-need evaluator. Arguments are not reduced before the substitution,
+/// but bound to a thunk that is evaluated on the first use and then updated in place.
+/// The thunks are only kept for the report if it was asked for, otherwise they are dropped
+/// as soon as nothing refers to them
+#[derive(Default)]
+pub struct CallByNeed {
+    fuel: Option<usize>,
+    deadline: Option<Instant>,
+    steps: usize,
+    recording: bool,
+    thunks: Vec<(Rc<DeBruijn>, Rc<Thunk>)>,
+}
+
+impl CallByNeed {
+    pub fn new() -> Self {
+        CallByNeed::default()
+    }
+
+    /// Return a new evaluator limited to the given number of beta reductions
+    pub fn with_fuel(self, fuel: usize) -> Self {
+        CallByNeed {
+            fuel: Some(fuel),
+            ..self
+        }
+    }
+
+    /// Return a new evaluator that stops at the given point in time
+    pub fn with_deadline(self, deadline: Instant) -> Self {
+        CallByNeed {
+            deadline: Some(deadline),
+            ..self
+        }
+    }
+
+    /// Return a new evaluator that keeps every thunk it creates for the report
+    pub fn with_report(self) -> Self {
+        CallByNeed {
+            recording: true,
+            ..self
+        }
+    }
+
+    /// Counters of every argument created so far, in the order they were created.
+    /// Empty unless the evaluator was created with the report
+    pub fn report(&self) -> Vec<ThunkReport> {
+        self.thunks
+            .iter()
+            .map(|(term, thunk)| ThunkReport {
+                argument: term.to_named(&VarGen::new()),
+                forced: thunk.forced.get(),
+                evaluated: thunk.evaluated.get(),
+            })
+            .collect()
+    }
+
+    /// Reduce the term to its normal form. Abstractions are normalized by applying them
+    /// to a fresh variable, so the arguments are still shared under binders
+    pub fn normalize(&mut self, ast: &AST) -> Result<AST, NeedError> {
+        let term = ast.to_de_bruijn();
+        let value = self.eval(&term, &Env::new())?;
+        let normal = self.read_back(value, 0)?;
+        Ok(normal.to_named(&VarGen::new()))
+    }
+
+    /// Reduce the term to its normal form or until a limit is hit. The evaluation has no
+    /// intermediate terms, so a reduction that is stopped carries the term it started from
+    pub fn run(ast: &AST, limits: &ReductionLimits) -> ReductionResult {
+        let machine = CallByNeed::new();
+        let machine = match limits.fuel {
+            Some(fuel) => machine.with_fuel(fuel),
+            None => machine,
+        };
+        let mut machine = match limits.deadline {
+            Some(deadline) => machine.with_deadline(deadline),
+            None => machine,
+        };
+        match machine.normalize(ast) {
+            Ok(normal) => ReductionResult::Normalized(normal),
+            Err(NeedError::OutOfFuel(steps)) => ReductionResult::OutOfFuel(ast.clone(), steps),
+            Err(NeedError::TimedOut) => ReductionResult::TimedOut(ast.clone()),
+        }
+    }
+
+    /// Evaluate a term to weak head normal form in the environment
+    fn eval(&mut self, term: &DeBruijn, env: &Env) -> Result<Value, NeedError> {
+        let mut term = Rc::new(term.clone());
+        let mut env = env.clone();
+        // Entering the body of a closure is a loop, so a long chain of calls uses no stack
+        loop {
+            match &*term {
+                DeBruijn::Bound(index) => return self.force(&env.get(*index)),
+                DeBruijn::Free(s) => return Ok(Value::Neutral(Head::Free(s.clone()), Vec::new())),
+                DeBruijn::Abstr(body) => return Ok(Value::Closure(body.clone(), env)),
+                // The argument is suspended, so it is only evaluated if the function uses it
+                DeBruijn::Apply(f, arg) => {
+                    let function = self.eval(f, &env)?;
+                    let thunk = Rc::new(Thunk::suspended(arg.clone(), env.clone()));
+                    if self.recording {
+                        self.thunks.push((arg.clone(), thunk.clone()));
+                    }
+                    match function {
+                        Value::Closure(body, closure_env) => {
+                            if self.fuel.is_some_and(|fuel| self.steps >= fuel) {
+                                return Err(NeedError::OutOfFuel(self.steps));
+                            }
+                            if self
+                                .deadline
+                                .is_some_and(|deadline| Instant::now() >= deadline)
+                            {
+                                return Err(NeedError::TimedOut);
+                            }
+                            self.steps += 1;
+                            env = closure_env.with(thunk);
+                            term = body;
+                        }
+                        Value::Neutral(head, mut args) => {
+                            args.push(thunk);
+                            return Ok(Value::Neutral(head, args));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Demand the value of a thunk. It is computed on the first demand and then reused
+    fn force(&mut self, thunk: &Rc<Thunk>) -> Result<Value, NeedError> {
+        thunk.forced.set(thunk.forced.get() + 1);
+        let state = thunk.state.replace(ThunkState::Evaluating);
+        match state {
+            ThunkState::Evaluated(value) => {
+                thunk.state.replace(ThunkState::Evaluated(value.clone()));
+                Ok(value)
+            }
+            ThunkState::Suspended(term, env) => {
+                thunk.evaluated.set(thunk.evaluated.get() + 1);
+                let value = self.eval(&term, &env)?;
+                // Update the thunk in place, so every other occurrence sees the value
+                thunk.state.replace(ThunkState::Evaluated(value.clone()));
+                Ok(value)
+            }
+            // Without recursive bindings a thunk is never in its own environment
+            ThunkState::Evaluating => panic!("A thunk cannot demand its own value"),
+        }
+    }
+
+    /// Turn a value back into a term in normal form at the given depth of binders
+    fn read_back(&mut self, value: Value, depth: usize) -> Result<DeBruijn, NeedError> {
+        match value {
+            // Apply the closure to a variable that stands for its parameter and normalize the body
+            Value::Closure(body, env) => {
+                let var = Value::Neutral(Head::Level(depth), Vec::new());
+                let env = env.with(Rc::new(Thunk::evaluated(var)));
+                let value = self.eval(&body, &env)?;
+                Ok(DeBruijn::Abstr(Rc::new(self.read_back(value, depth + 1)?)))
+            }
+            Value::Neutral(head, args) => {
+                let head = match head {
+                    Head::Free(s) => DeBruijn::Free(s),
+                    Head::Level(level) => DeBruijn::Bound(depth - 1 - level),
+                };
+                args.iter().try_fold(head, |term, thunk| {
+                    let value = self.force(thunk)?;
+                    let arg = self.read_back(value, depth)?;
+                    Ok(DeBruijn::Apply(Rc::new(term), Rc::new(arg)))
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CallByNeed;
+    use crate::ast::AST;
+    use crate::beta::{ReductionLimits, ReductionResult};
+    use std::time::Duration;
+
+    fn parse(input: &str) -> AST {
+        AST::eval(input).unwrap()
+    }
+
+    #[test]
+    fn normalize() {
+        let mut machine = CallByNeed::new();
+        let result = machine
+            .normalize(&parse("($x, y -> y x) a ($z -> z)"))
+            .unwrap();
+        assert_eq!(result, parse("a"));
+        // Abstractions are normalized under their binders
+        let result = machine.normalize(&parse("$x -> ($y -> y) x")).unwrap();
+        assert_eq!(result, parse("$x -> x"));
+    }
+
+    #[test]
+    fn shared_argument_is_evaluated_once() {
+        let mut machine = CallByNeed::new().with_report();
+        let result = machine.normalize(&parse("($x -> x x) (($y -> y) ($z -> z))"));
+        assert_eq!(result.unwrap(), parse("$z -> z"));
+        let report = machine.report();
+        let shared = report
+            .iter()
+            .find(|report| report.argument == parse("($y -> y) ($z -> z)"))
+            .expect("The argument has a thunk");
+        assert_eq!(shared.forced, 2);
+        assert_eq!(shared.evaluated, 1);
+        assert!(report.iter().all(|report| report.evaluated <= 1));
+    }
+
+    #[test]
+    fn unused_argument_is_never_evaluated() {
+        let mut machine = CallByNeed::new().with_report();
+        let result = machine.normalize(&parse("($x, y -> y) (($x -> x x) ($x -> x x)) a"));
+        assert_eq!(result.unwrap(), parse("a"));
+        let diverging = &machine.report()[0];
+        assert_eq!(diverging.argument, parse("($x -> x x) ($x -> x x)"));
+        assert_eq!((diverging.forced, diverging.evaluated), (0, 0));
+    }
+
+    #[test]
+    fn thunks_are_only_kept_for_the_report() {
+        let mut machine = CallByNeed::new();
+        machine
+            .normalize(&parse("($x -> x x) (($y -> y) ($z -> z))"))
+            .unwrap();
+        assert!(machine.report().is_empty());
+    }
+
+    #[test]
+    fn out_of_fuel() {
+        let mut machine = CallByNeed::new().with_fuel(10);
+        assert!(machine
+            .normalize(&parse("($x -> x x) ($x -> x x)"))
+            .is_err());
+    }
+
+    #[test]
+    fn run_within_limits() {
+        let omega = parse("($x -> x x) ($x -> x x)");
+        let limits = ReductionLimits::new().with_timeout(Duration::from_millis(100));
+        assert!(matches!(
+            CallByNeed::run(&omega, &limits),
+            ReductionResult::TimedOut(_)
+        ));
+        let limits = ReductionLimits {
+            fuel: Some(10),
+            deadline: None,
+        };
+        assert!(matches!(
+            CallByNeed::run(&omega, &limits),
+            ReductionResult::OutOfFuel(_, 10)
+        ));
+        match CallByNeed::run(&parse("($x -> x x) (($y -> y) a)"), &limits) {
+            ReductionResult::Normalized(ast) => assert_eq!(ast, parse("a a")),
+            result => panic!("Expected a normal form, got {}", result),
+        }
+    }
+}
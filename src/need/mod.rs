@@ -0,0 +1,7 @@
+//! This module implements call-by-need evaluation, where every argument becomes a shared thunk
+//! that is evaluated at most once and updated in place with its value
+pub mod machine;
+pub mod thunk;
+
+pub use self::machine::CallByNeed;
+pub use self::thunk::{Env, Thunk, Value};
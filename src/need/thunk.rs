@@ -0,0 +1,96 @@
+use crate::debruijn::DeBruijn;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+/// Type represents an environment, i.e. the thunks bound to de Bruijn indices.
+/// The innermost binding is the head of the list, so index 0 is the first node
+#[derive(Clone, Default)]
+pub struct Env {
+    head: Option<Rc<EnvNode>>,
+}
+
+struct EnvNode {
+    thunk: Rc<Thunk>,
+    tail: Env,
+}
+
+impl Env {
+    pub fn new() -> Self {
+        Env { head: None }
+    }
+
+    /// Return a new environment with the thunk bound to index 0
+    pub fn with(&self, thunk: Rc<Thunk>) -> Self {
+        Env {
+            head: Some(Rc::new(EnvNode {
+                thunk,
+                tail: self.clone(),
+            })),
+        }
+    }
+
+    /// Look up the thunk bound to the index
+    pub fn get(&self, index: usize) -> Rc<Thunk> {
+        let mut env = self;
+        for _ in 0..index {
+            env = &env.head.as_ref().expect("Unbound de Bruijn index").tail;
+        }
+        env.head
+            .as_ref()
+            .expect("Unbound de Bruijn index")
+            .thunk
+            .clone()
+    }
+}
+
+/// Type represents a term evaluated to weak head normal form
+///  - *Closure*: the body of an abstraction together with the environment it was created in
+///  - *Neutral*: a variable that cannot be reduced, applied to arguments. The head is either
+///    a free variable or a bound variable of a normalized abstraction identified by its level
+#[derive(Clone)]
+pub enum Value {
+    Closure(Rc<DeBruijn>, Env),
+    Neutral(Head, Vec<Rc<Thunk>>),
+}
+
+#[derive(Clone)]
+pub enum Head {
+    Free(String),
+    Level(usize),
+}
+
+/// Type represents the state of a thunk
+///  - *Suspended*: the term has not been evaluated yet
+///  - *Evaluating*: the term is being evaluated
+///  - *Evaluated*: the value that replaced the term after the first evaluation
+pub enum ThunkState {
+    Suspended(Rc<DeBruijn>, Env),
+    Evaluating,
+    Evaluated(Value),
+}
+
+/// Type represents a suspended argument, shared by every occurrence of the variable it is bound to.
+/// It counts how many times its value was demanded and how many times it was actually computed
+pub struct Thunk {
+    pub state: RefCell<ThunkState>,
+    pub forced: Cell<usize>,
+    pub evaluated: Cell<usize>,
+}
+
+impl Thunk {
+    pub fn suspended(term: Rc<DeBruijn>, env: Env) -> Self {
+        Thunk {
+            state: RefCell::new(ThunkState::Suspended(term, env)),
+            forced: Cell::new(0),
+            evaluated: Cell::new(0),
+        }
+    }
+
+    pub fn evaluated(value: Value) -> Self {
+        Thunk {
+            state: RefCell::new(ThunkState::Evaluated(value)),
+            forced: Cell::new(0),
+            evaluated: Cell::new(0),
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use crate::lexer::{Token, TokenKind};
+use crate::parser::ParsingError;
+use crate::span::Span;
+use crate::types::Type;
+
+/// Parser of a single type, i.e. a base type or a type in parentheses
+fn atom(tokens: &[Token]) -> Result<(Type, &[Token]), ParsingError> {
+    match tokens {
+        [Token {
+            kind: TokenKind::Var(s),
+            ..
+        }, rest @ ..] => Ok((Type::Base(s.clone()), rest)),
+        // Inside parentheses every arrow belongs to the type
+        [Token {
+            kind: TokenKind::LParen,
+            span,
+        }, rest @ ..] => {
+            let (ty, rest) = type_expression(rest)?;
+            match rest {
+                [Token {
+                    kind: TokenKind::RParen,
+                    ..
+                }, rest @ ..] => Ok((ty, rest)),
+                [token, ..] => Err(ParsingError::MismatchedParens(
+                    format!("Expected ')' after type but found {}", token.kind),
+                    token.span,
+                )),
+                [] => Err(ParsingError::MismatchedParens(
+                    "Unclosed '(' at the end of input".to_string(),
+                    *span,
+                )),
+            }
+        }
+        [token, ..] => Err(ParsingError::InvalidLambda(
+            format!("Expected a type, found {}", token.kind),
+            token.span,
+        )),
+        [] => Err(ParsingError::InvalidLambda(
+            "Unexpected end of input when reading a type".to_string(),
+            Span::default(),
+        )),
+    }
+}
+
+/// Parser of the variable of a universal quantifier, leaving its body in the rest
+fn quantified(tokens: &[Token]) -> Result<(String, &[Token]), ParsingError> {
+    match tokens {
+        [Token {
+            kind: TokenKind::Var(name),
+            ..
+        }, Token {
+            kind: TokenKind::Dot,
+            ..
+        }, rest @ ..] => Ok((name.clone(), rest)),
+        [token, ..] => Err(ParsingError::InvalidSyntax(
+            format!(
+                "Expected a type variable and '.' after '\\/', found {}",
+                token.kind
+            ),
+            token.span,
+        )),
+        [] => Err(ParsingError::InvalidSyntax(
+            "Unexpected end of input when reading a type".to_string(),
+            Span::default(),
+        )),
+    }
+}
+
+/// Parser of a type where every arrow is part of it, i.e. A -> B -> C => A -> (B -> C)
+pub(crate) fn type_expression(tokens: &[Token]) -> Result<(Type, &[Token]), ParsingError> {
+    if let [Token {
+        kind: TokenKind::Forall,
+        ..
+    }, rest @ ..] = tokens
+    {
+        let (name, rest) = quantified(rest)?;
+        let (body, rest) = type_expression(rest)?;
+        return Ok((Type::forall(name, body), rest));
+    }
+    let (from, rest) = atom(tokens)?;
+    match rest {
+        [Token {
+            kind: TokenKind::Arrow,
+            ..
+        }, rest @ ..] => {
+            let (to, rest) = type_expression(rest)?;
+            Ok((Type::arrow(from, to), rest))
+        }
+        _ => Ok((from, rest)),
+    }
+}
+
+/// Whether an arrow followed by these tokens still belongs to the type of a parameter.
+/// It does if a quantifier or another type follows, which again ends with '->' or ','
+fn continues(tokens: &[Token]) -> bool {
+    if let [Token {
+        kind: TokenKind::Forall,
+        ..
+    }, ..] = tokens
+    {
+        return true;
+    }
+    matches!(
+        atom(tokens),
+        Ok((
+            _,
+            [
+                Token {
+                    kind: TokenKind::Arrow | TokenKind::Comma,
+                    ..
+                },
+                ..
+            ]
+        ))
+    )
+}
+
+/// Parser of the type annotation of a lambda parameter. The arrow that ends the parameters
+/// is not part of the type, so $x: A -> B -> x reads the type A -> B and the body x
+pub(crate) fn annotation(tokens: &[Token]) -> Result<(Type, &[Token]), ParsingError> {
+    if let [Token {
+        kind: TokenKind::Forall,
+        ..
+    }, rest @ ..] = tokens
+    {
+        let (name, rest) = quantified(rest)?;
+        let (body, rest) = annotation(rest)?;
+        return Ok((Type::forall(name, body), rest));
+    }
+    let (from, rest) = atom(tokens)?;
+    match rest {
+        [Token {
+            kind: TokenKind::Arrow,
+            ..
+        }, after @ ..]
+            if continues(after) =>
+        {
+            let (to, rest) = annotation(after)?;
+            Ok((Type::arrow(from, to), rest))
+        }
+        _ => Ok((from, rest)),
+    }
+}
@@ -1,4 +1,7 @@
 //! Parser module that build an AST from a flat sequence of tokens
+pub mod annotation;
 pub mod parser;
+pub mod statement;
 
 pub use self::parser::{parse, ParsingError};
+pub use self::statement::Statement;
@@ -1,19 +1,45 @@
 use crate::ast::Term;
 use crate::ast::AST;
-use crate::lexer::Token;
+use crate::lexer::{Token, TokenKind};
+use crate::parser::annotation::annotation;
+use crate::span::{render_snippet, Span};
 
-/// Type for errors during parsing
+/// Type for errors during parsing, each one points at the offending part of the input
 #[derive(Debug)]
 pub enum ParsingError {
-    MismatchedParens(String),
-    InvalidSyntax(String),
-    InvalidLambda(String),
+    MismatchedParens(String, Span),
+    InvalidSyntax(String, Span),
+    InvalidLambda(String, Span),
+}
+
+impl ParsingError {
+    pub fn message(&self) -> &str {
+        match self {
+            ParsingError::MismatchedParens(message, _)
+            | ParsingError::InvalidSyntax(message, _)
+            | ParsingError::InvalidLambda(message, _) => message,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            ParsingError::MismatchedParens(_, span)
+            | ParsingError::InvalidSyntax(_, span)
+            | ParsingError::InvalidLambda(_, span) => *span,
+        }
+    }
+
+    /// Render the error as an annotated snippet of the input it was produced from
+    pub fn render(&self, source: &str) -> String {
+        render_snippet(source, self.span(), self.message())
+    }
 }
 
 /// Type represents different states of the parser
 ///  - *Continue*: represents an intermediate result of parsing
 ///  - *Stop*: represents the final result of paring
-///  - *End*: represents that end of an expression has been reached
+///  - *End*: represents that end of an expression has been reached. The token that ended it
+///    is left in the slice for the caller to check
 #[derive(Debug)]
 pub enum ParserState<'a> {
     Continue(AST, &'a [Token]),
@@ -21,60 +47,173 @@ pub enum ParserState<'a> {
     End(&'a [Token]),
 }
 
+/// Span of a term built by the parser
+fn span_of(ast: &AST) -> Span {
+    ast.span.unwrap_or_default()
+}
+
+/// Error for an expression that ended before it started, pointing at the token that ended it
+fn missing_expression(tokens: &[Token], context: &str) -> ParsingError {
+    match tokens {
+        [Token {
+            kind: TokenKind::RParen,
+            span,
+        }, ..] => ParsingError::MismatchedParens(format!("Unexpected ')' {}", context), *span),
+        [token, ..] => ParsingError::InvalidSyntax(
+            format!("Unexpected {} {}", token.kind, context),
+            token.span,
+        ),
+        [] => ParsingError::InvalidSyntax(
+            format!("Unexpected end of input {}", context),
+            Span::default(),
+        ),
+    }
+}
+
 /// Base parser that parses valid lambda expressions
-fn parse_expression(tokens: &[Token]) -> Result<ParserState, ParsingError> {
+fn parse_expression(tokens: &[Token]) -> Result<ParserState<'_>, ParsingError> {
     match tokens {
         // Create a variable node and continue parsing
-        [Token::Var(s), rest @ ..] => Ok(ParserState::Continue(AST::var(s.clone()), rest)),
+        [Token {
+            kind: TokenKind::Var(s),
+            span,
+        }, rest @ ..] => Ok(ParserState::Continue(
+            AST::var(s.clone()).with_span(*span),
+            rest,
+        )),
         // Opening parenthesis -> open a group
-        [Token::LParen, rest @ ..] => group(rest),
+        [Token {
+            kind: TokenKind::LParen,
+            span,
+        }, rest @ ..] => group(*span, rest),
         // Start of a lambda expression -> start parsing parameters
-        [Token::Lambda, rest @ ..] => lambda(rest),
-        // Right parenthesis -> signal end of expression
-        [Token::RParen, rest @ ..] => Ok(ParserState::End(rest)),
-        [] => Ok(ParserState::End(&[])),
-        _ => Err(ParsingError::InvalidSyntax(format!(
-            "Unexpected token in expression body: {:?}",
-            tokens[0]
-        ))),
+        [Token {
+            kind: TokenKind::Lambda,
+            span,
+        }, rest @ ..] => lambda(*span, rest),
+        // Start of a let binding -> read the binding and its body
+        [Token {
+            kind: TokenKind::Let,
+            span,
+        }, rest @ ..] => let_binding(*span, rest),
+        // Right parenthesis, 'in', end of statement or end of input -> signal end of expression
+        [Token {
+            kind: TokenKind::RParen | TokenKind::In | TokenKind::Separator | TokenKind::Eof,
+            ..
+        }, ..]
+        | [] => Ok(ParserState::End(tokens)),
+        [token, ..] => Err(ParsingError::InvalidSyntax(
+            format!("Unexpected token in expression body: {}", token.kind),
+            token.span,
+        )),
     }
 }
 
 /// Parser handling the parameters of a lambda expression
-fn parse_params(tokens: &[Token]) -> Result<ParserState, ParsingError> {
+fn parse_params(tokens: &[Token]) -> Result<ParserState<'_>, ParsingError> {
     match tokens {
         // Variable and a comma -> continue processing parameters
-        [Token::Var(s), Token::Comma, rest @ ..] => {
-            Ok(ParserState::Continue(AST::var(s.clone()), rest))
-        }
+        [Token {
+            kind: TokenKind::Var(s),
+            span,
+        }, Token {
+            kind: TokenKind::Comma,
+            ..
+        }, rest @ ..] => Ok(ParserState::Continue(
+            AST::var(s.clone()).with_span(*span),
+            rest,
+        )),
         // Variable and an arrow -> stop processing parameters
-        [Token::Var(s), Token::Arrow, rest @ ..] => {
-            Ok(ParserState::Stop(AST::var(s.clone()), rest))
+        [Token {
+            kind: TokenKind::Var(s),
+            span,
+        }, Token {
+            kind: TokenKind::Arrow,
+            ..
+        }, rest @ ..] => Ok(ParserState::Stop(
+            AST::var(s.clone()).with_span(*span),
+            rest,
+        )),
+        // Variable and a colon -> read the type of the parameter, which ends the same way
+        [Token {
+            kind: TokenKind::Var(s),
+            span,
+        }, Token {
+            kind: TokenKind::Colon,
+            ..
+        }, rest @ ..] => {
+            let (ty, rest) = annotation(rest)?;
+            let param = AST::var(s.clone()).with_span(*span).with_annotation(ty);
+            match rest {
+                [Token {
+                    kind: TokenKind::Comma,
+                    ..
+                }, rest @ ..] => Ok(ParserState::Continue(param, rest)),
+                [Token {
+                    kind: TokenKind::Arrow,
+                    ..
+                }, rest @ ..] => Ok(ParserState::Stop(param, rest)),
+                [token, ..] => Err(ParsingError::InvalidLambda(
+                    format!(
+                        "Expected ',' or '->' after the type of lambda param, found {}",
+                        token.kind
+                    ),
+                    token.span,
+                )),
+                [] => Err(ParsingError::InvalidLambda(
+                    "Unexpected end of input when reading lambda params".to_string(),
+                    Span::default(),
+                )),
+            }
         }
-        [] => Err(ParsingError::InvalidLambda(format!(
-            "Unexpected end of file when reading lambda params"
-        ))),
-        _ => Err(ParsingError::InvalidSyntax(format!(
-            "Unexpected token in lambda params: {:?}",
-            tokens[0]
-        ))),
+        // Variable followed by anything else -> the parameter list is not finished properly
+        [Token {
+            kind: TokenKind::Var(_),
+            ..
+        }, token, ..] => Err(ParsingError::InvalidLambda(
+            format!(
+                "Expected ',' or '->' after lambda param, found {}",
+                token.kind
+            ),
+            token.span,
+        )),
+        [Token {
+            kind: TokenKind::Eof,
+            span,
+        }, ..] => Err(ParsingError::InvalidLambda(
+            "Unexpected end of input when reading lambda params".to_string(),
+            *span,
+        )),
+        [token, ..] => Err(ParsingError::InvalidSyntax(
+            format!("Unexpected token in lambda params: {}", token.kind),
+            token.span,
+        )),
+        [] => Err(ParsingError::InvalidLambda(
+            "Unexpected end of input when reading lambda params".to_string(),
+            Span::default(),
+        )),
     }
 }
 
 /// Function handling building of application terms
 /// It uses recursion to handle chained applications
-fn apply(term: AST, tokens: &[Token]) -> Result<ParserState, ParsingError> {
+fn apply(term: AST, tokens: &[Token]) -> Result<ParserState<'_>, ParsingError> {
     let result = parse_expression(tokens)?;
     match result {
-        // Right parens found -> return the left term (typically a variable)
+        // End of expression found -> return the left term (typically a variable)
         ParserState::End(rest) => Ok(ParserState::Stop(term, rest)),
         // Right term stopped evaluating -> build the application from the two terms
         ParserState::Stop(new_term, rest) => {
-            Ok(ParserState::Stop(AST::apply(term, new_term), rest))
+            let span = span_of(&term).join(span_of(&new_term));
+            Ok(ParserState::Stop(
+                AST::apply(term, new_term).with_span(span),
+                rest,
+            ))
         }
         // Intermediate result -> recurse to process more tokens
         ParserState::Continue(new_term, rest) => {
-            let new_result = apply(AST::apply(term, new_term), rest)?;
+            let span = span_of(&term).join(span_of(&new_term));
+            let new_result = apply(AST::apply(term, new_term).with_span(span), rest)?;
             match new_result {
                 // Apply has no path where it return a "Stop"
                 ParserState::End(_) => {
@@ -93,7 +232,8 @@ fn apply(term: AST, tokens: &[Token]) -> Result<ParserState, ParsingError> {
 
 /// Function handling chaining parameters recursively, i.e.
 /// $xy -> x => $x -> $y -> x
-fn lambda(tokens: &[Token]) -> Result<ParserState, ParsingError> {
+/// The start is the span where the abstraction begins, i.e. the '$' or the parameter
+fn lambda(start: Span, tokens: &[Token]) -> Result<ParserState<'_>, ParsingError> {
     let result = parse_params(tokens)?;
     match result {
         // Parse_params has no path where it return a "End"
@@ -101,46 +241,64 @@ fn lambda(tokens: &[Token]) -> Result<ParserState, ParsingError> {
             panic!("A call to 'parse_params' should never return a 'End' result")
         }
         // Found the final parameter -> start building the abstraction body
-        ParserState::Stop(term, rest) => abstr(term, rest),
+        ParserState::Stop(term, rest) => abstr(start, term, rest),
         // A parameter ends with a coma -> recursively process more
         ParserState::Continue(term, rest) => match term.term {
             Term::Var(_) => {
-                let new_result = lambda(rest)?;
+                let new_result = lambda(span_of(&term), rest)?;
                 match new_result {
                     // Lambda has no path where it return a "End"
                     ParserState::End(_) => {
                         panic!("A call to 'lambda' should never return a 'End' result")
                     }
                     // Found the final parameter -> prepare the "container" abstraction
+                    // The body has already been read as far as possible, so the abstraction is final
                     ParserState::Stop(new_term, new_rest) => {
-                        Ok(ParserState::Continue(AST::abstr(term, new_term), new_rest))
+                        let span = start.join(span_of(&new_term));
+                        Ok(ParserState::Stop(
+                            AST::abstr(term, new_term).with_span(span),
+                            new_rest,
+                        ))
                     }
                     // Found the next parameter -> prepare the "container" abstraction
                     ParserState::Continue(new_term, new_rest) => {
-                        Ok(ParserState::Continue(AST::abstr(term, new_term), new_rest))
+                        let span = start.join(span_of(&new_term));
+                        Ok(ParserState::Stop(
+                            AST::abstr(term, new_term).with_span(span),
+                            new_rest,
+                        ))
                     }
                 }
             }
-            _ => Err(ParsingError::InvalidLambda(format!(
-                "Lambda expression arguments may only contain variables, found {:?}",
-                term
-            ))),
+            _ => Err(ParsingError::InvalidLambda(
+                format!(
+                    "Lambda expression arguments may only contain variables, found {:?}",
+                    term
+                ),
+                span_of(&term),
+            )),
         },
     }
 }
 
 /// Function handling building of abstraction terms
 /// It recurses into apply() to read the whole lambda body
-fn abstr(arg: AST, tokens: &[Token]) -> Result<ParserState, ParsingError> {
+fn abstr(start: Span, arg: AST, tokens: &[Token]) -> Result<ParserState<'_>, ParsingError> {
     let result = parse_expression(tokens)?;
     match result {
-        // Lamdba body can't start with a right paren
-        ParserState::End(_) => Err(ParsingError::MismatchedParens(format!(
-            "Unexpected ')' at the start of a lambda expression after {:?}",
-            arg
-        ))),
+        // Lamdba body can't start with a right paren or be empty
+        ParserState::End(rest) => Err(missing_expression(
+            rest,
+            "at the start of a lambda expression body",
+        )),
         // Body finished parsing -> return the lambda with the term as the body
-        ParserState::Stop(new_term, rest) => Ok(ParserState::Stop(AST::abstr(arg, new_term), rest)),
+        ParserState::Stop(new_term, rest) => {
+            let span = start.join(span_of(&new_term));
+            Ok(ParserState::Stop(
+                AST::abstr(arg, new_term).with_span(span),
+                rest,
+            ))
+        }
         // If the body did not finish, it means it is an application of multiple terms
         ParserState::Continue(new_term, rest) => {
             let new_result = apply(new_term, rest)?;
@@ -151,7 +309,11 @@ fn abstr(arg: AST, tokens: &[Token]) -> Result<ParserState, ParsingError> {
                 }
                 // Processing of body finished -> return the final abstraction
                 ParserState::Stop(final_term, final_rest) => {
-                    Ok(ParserState::Stop(AST::abstr(arg, final_term), final_rest))
+                    let span = start.join(span_of(&final_term));
+                    Ok(ParserState::Stop(
+                        AST::abstr(arg, final_term).with_span(span),
+                        final_rest,
+                    ))
                 }
                 // Apply has no path where it return a "Continue"
                 ParserState::Continue(_, _) => {
@@ -163,46 +325,109 @@ fn abstr(arg: AST, tokens: &[Token]) -> Result<ParserState, ParsingError> {
 }
 
 /// Function that handles expressions grouped in parentheses ()
-/// It recurses into apply() to read the entire expression
-fn group(tokens: &[Token]) -> Result<ParserState, ParsingError> {
-    let result = parse_expression(tokens)?;
-    match result {
-        ParserState::End(_) => Err(ParsingError::MismatchedParens(format!(
-            "Empty parentheses '()' are not permitted"
-        ))),
-        // If the final expression is found, turn it into "Continue" so the caller can read more tokens
-        ParserState::Stop(term, rest) => Ok(ParserState::Continue(term, rest)),
-        // If the final expression is not found, use "apply" to build it from more tokens
-        ParserState::Continue(term, rest) => {
-            let new_result = apply(term, rest)?;
-            match new_result {
-                // Apply has no path where it return an "End"
-                ParserState::End(_) => {
-                    panic!("Recursive call to 'apply' should never return an 'End' result")
-                }
-                // If the final expression is found, turn it into "Continue" so the caller can read more tokens
-                ParserState::Stop(final_term, final_rest) => {
-                    Ok(ParserState::Continue(final_term, final_rest))
-                }
-                // Apply has no path where it return a "Continue"
-                ParserState::Continue(_, _) => {
-                    panic!("Recursive call to 'apply' should never return a 'Continue' result")
+/// It reads the entire expression and then expects the closing paren
+fn group(lparen: Span, tokens: &[Token]) -> Result<ParserState<'_>, ParsingError> {
+    let unclosed =
+        || ParsingError::MismatchedParens("Unclosed '(' at the end of input".to_string(), lparen);
+    let (term, rest) = match tokens {
+        [Token {
+            kind: TokenKind::RParen,
+            span,
+        }, ..] => {
+            return Err(ParsingError::MismatchedParens(
+                "Empty parentheses '()' are not permitted".to_string(),
+                lparen.join(*span),
+            ))
+        }
+        [Token {
+            kind: TokenKind::Eof,
+            ..
+        }, ..]
+        | [] => return Err(unclosed()),
+        _ => expression(tokens, "after '('")?,
+    };
+    match rest {
+        // Consume the closing paren and turn the result into "Continue" so the caller can read more tokens
+        [Token {
+            kind: TokenKind::RParen,
+            span,
+        }, rest @ ..] => Ok(ParserState::Continue(
+            term.with_span(lparen.join(*span)),
+            rest,
+        )),
+        // The expression ended without closing the group
+        [Token {
+            kind: TokenKind::Eof,
+            ..
+        }, ..]
+        | [] => Err(unclosed()),
+        [token, ..] => Err(ParsingError::MismatchedParens(
+            format!("Expected ')' but found {}", token.kind),
+            token.span,
+        )),
+    }
+}
+
+/// Function handling let bindings, which are desugared into a redex marked as a let, i.e.
+/// let x = a in b => ($x -> b) a
+/// The body extends as far as possible, the same as the body of an abstraction
+fn let_binding(start: Span, tokens: &[Token]) -> Result<ParserState<'_>, ParsingError> {
+    match tokens {
+        [Token {
+            kind: TokenKind::Var(s),
+            span,
+        }, Token {
+            kind: TokenKind::Equals,
+            ..
+        }, rest @ ..] => {
+            let param = AST::var(s.clone()).with_span(*span);
+            let (value, rest) = expression(rest, "after '=' in let binding")?;
+            match rest {
+                [Token {
+                    kind: TokenKind::In,
+                    ..
+                }, rest @ ..] => {
+                    let (body, rest) = expression(rest, "at the start of let body")?;
+                    let span = start.join(span_of(&body));
+                    let function = AST::abstr(param, body).with_span(span);
+                    Ok(ParserState::Stop(
+                        AST::apply(function, value).with_span(span).into_let(),
+                        rest,
+                    ))
                 }
+                [token, ..] => Err(ParsingError::InvalidSyntax(
+                    format!("Expected 'in' after let binding, found {}", token.kind),
+                    token.span,
+                )),
+                [] => Err(missing_expression(rest, "in let binding")),
             }
         }
+        [Token {
+            kind: TokenKind::Var(_),
+            ..
+        }, token, ..] => Err(ParsingError::InvalidSyntax(
+            format!("Expected '=' after let variable, found {}", token.kind),
+            token.span,
+        )),
+        [token, ..] => Err(ParsingError::InvalidSyntax(
+            format!("Expected a variable after 'let', found {}", token.kind),
+            token.span,
+        )),
+        [] => Err(missing_expression(tokens, "after 'let'")),
     }
 }
 
-/// Main parsing function. It starts with the first expression and then recurses into
-/// apply() to read the entire expression
-pub fn parse(tokens: &[Token]) -> Result<AST, ParsingError> {
+/// Function reading a whole expression up to the token that ends it, which is left in the rest.
+/// It starts with the first expression and then recurses into apply() to chain more
+pub(super) fn expression<'a>(
+    tokens: &'a [Token],
+    context: &str,
+) -> Result<(AST, &'a [Token]), ParsingError> {
     let result = parse_expression(tokens)?;
     match result {
-        ParserState::End(_) => Err(ParsingError::MismatchedParens(format!(
-            "Unexpected ')' at the start of expression"
-        ))),
-        // If the result is found, return it
-        ParserState::Stop(term, _) => Ok(term),
+        ParserState::End(rest) => Err(missing_expression(rest, context)),
+        // The final expression is found
+        ParserState::Stop(term, rest) => Ok((term, rest)),
         // Otherwise use apply to chain more expressions
         ParserState::Continue(new_term, rest) => {
             let new_result = apply(new_term, rest)?;
@@ -212,7 +437,7 @@ pub fn parse(tokens: &[Token]) -> Result<AST, ParsingError> {
                     panic!("Recursive call to 'apply' should never return an 'End' result")
                 }
                 // Finished AST has been found
-                ParserState::Stop(final_term, _) => Ok(final_term),
+                ParserState::Stop(final_term, final_rest) => Ok((final_term, final_rest)),
                 // Apply has no path where it return a "Continue"
                 ParserState::Continue(_, _) => {
                     panic!("Recursive call to 'apply' should never return a 'Continue' result")
@@ -221,3 +446,91 @@ pub fn parse(tokens: &[Token]) -> Result<AST, ParsingError> {
         }
     }
 }
+
+/// Error for a token left over after an expression has been read
+pub(crate) fn trailing_token(token: &Token) -> ParsingError {
+    match token.kind {
+        // A right paren left over means it does not have a matching left one
+        TokenKind::RParen => ParsingError::MismatchedParens(
+            "Unexpected ')' without a matching '('".to_string(),
+            token.span,
+        ),
+        _ => ParsingError::InvalidSyntax(
+            format!("Unexpected {} after the end of expression", token.kind),
+            token.span,
+        ),
+    }
+}
+
+/// Main parsing function. It reads the entire expression and checks that the input is finished
+pub fn parse(tokens: &[Token]) -> Result<AST, ParsingError> {
+    let (term, rest) = expression(tokens, "at the start of expression")?;
+    match rest {
+        []
+        | [Token {
+            kind: TokenKind::Eof,
+            ..
+        }, ..] => Ok(term),
+        [token, ..] => Err(trailing_token(token)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, ParsingError};
+    use crate::ast::AST;
+    use crate::encodings::build::{apply, lambda, var};
+    use crate::lexer::tokenize;
+    use crate::span::Span;
+
+    /// The error of parsing the input, which must tokenize
+    fn parse_error(input: &str) -> ParsingError {
+        let tokens = tokenize(input).unwrap();
+        parse(&tokens).unwrap_err()
+    }
+
+    #[test]
+    fn multi_param_lambda() {
+        let expected = lambda(&["x", "y"], apply(var("x"), [var("y")]));
+        assert_eq!(AST::eval("$x, y -> x y").unwrap(), expected);
+    }
+
+    #[test]
+    fn multi_param_lambda_in_parens() {
+        let function = lambda(&["x", "y"], apply(var("x"), [var("y")]));
+        let expected = apply(function, [var("a"), var("b")]);
+        assert_eq!(AST::eval("($x, y -> x y) a b").unwrap(), expected);
+    }
+
+    #[test]
+    fn multi_param_lambda_as_argument() {
+        let function = apply(var("a"), [lambda(&["x", "y"], var("x"))]);
+        let expected = apply(function, [var("b")]);
+        assert_eq!(AST::eval("(a $x, y -> x) b").unwrap(), expected);
+    }
+
+    #[test]
+    fn unclosed_paren() {
+        let input = "($x -> x";
+        let err = parse_error(input);
+        assert!(matches!(err, ParsingError::MismatchedParens(..)));
+        // The error points at the paren that was left open
+        assert_eq!(err.span(), Span::new(0, 1, 1, 1));
+        assert_eq!(
+            err.render(input),
+            "error: Unclosed '(' at the end of input\n --> 1:1\n  |\n1 | ($x -> x\n  | ^"
+        );
+    }
+
+    #[test]
+    fn missing_arrow() {
+        let input = "$x x";
+        let err = parse_error(input);
+        assert!(matches!(err, ParsingError::InvalidLambda(..)));
+        assert_eq!(err.span(), Span::new(3, 4, 1, 4));
+        assert_eq!(
+            err.render(input),
+            "error: Expected ',' or '->' after lambda param, found 'x'\n --> 1:4\n  |\n1 | $x x\n  |    ^"
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use crate::ast::ast::EvalError;
+use crate::ast::AST;
+use crate::lexer::{tokenize, Token, TokenKind};
+use crate::parser::parser::{expression, trailing_token};
+use crate::parser::ParsingError;
+
+/// Type represents a top-level statement
+///  - *Definition*: binds a name to a term, i.e. id = $x -> x
+///  - *Expression*: a term to evaluate
+#[derive(Debug, Clone)]
+pub enum Statement {
+    Definition(String, AST),
+    Expression(AST),
+}
+
+/// Parser of a single statement, leaving the token that ended it in the rest
+fn statement(tokens: &[Token]) -> Result<(Statement, &[Token]), ParsingError> {
+    match tokens {
+        // Name followed by '=' -> definition
+        [Token {
+            kind: TokenKind::Var(name),
+            ..
+        }, Token {
+            kind: TokenKind::Equals,
+            ..
+        }, rest @ ..] => {
+            let (term, rest) = expression(rest, "after '=' in definition")?;
+            Ok((Statement::Definition(name.clone(), term), rest))
+        }
+        // Anything else -> expression
+        _ => {
+            let (term, rest) = expression(tokens, "at the start of statement")?;
+            Ok((Statement::Expression(term), rest))
+        }
+    }
+}
+
+/// Parse a program, i.e. a sequence of statements separated by ';' or by starting a new line
+/// in the first column. Empty statements are skipped
+pub fn parse_program(tokens: &[Token]) -> Result<Vec<Statement>, ParsingError> {
+    let mut statements = Vec::new();
+    let mut tokens = tokens;
+    loop {
+        match tokens {
+            [Token {
+                kind: TokenKind::Separator,
+                ..
+            }, rest @ ..] => tokens = rest,
+            []
+            | [Token {
+                kind: TokenKind::Eof,
+                ..
+            }, ..] => return Ok(statements),
+            _ => {
+                let (new_statement, rest) = statement(tokens)?;
+                statements.push(new_statement);
+                tokens = match rest {
+                    // The statement has to be followed by the end of statement or input
+                    []
+                    | [Token {
+                        kind: TokenKind::Separator | TokenKind::Eof,
+                        ..
+                    }, ..] => rest,
+                    [token, ..] => return Err(trailing_token(token)),
+                };
+            }
+        }
+    }
+}
+
+/// Parse a single statement, which is either a definition or an expression
+pub fn parse_statement(tokens: &[Token]) -> Result<Statement, ParsingError> {
+    let (result, rest) = statement(tokens)?;
+    // Only separators may follow the statement
+    let rest = rest.iter().find(|token| token.kind != TokenKind::Separator);
+    match rest {
+        None
+        | Some(Token {
+            kind: TokenKind::Eof,
+            ..
+        }) => Ok(result),
+        Some(token) if token.kind == TokenKind::RParen => Err(trailing_token(token)),
+        Some(token) => Err(ParsingError::InvalidSyntax(
+            format!("Expected a single statement, found {}", token.kind),
+            token.span,
+        )),
+    }
+}
+
+impl Statement {
+    pub fn eval(input: &str) -> Result<Statement, EvalError> {
+        let tokens =
+            tokenize(input).map_err(|err| EvalError::TokenizationError(err.render(input)))?;
+        parse_statement(&tokens).map_err(|err| EvalError::ParsingError(err.render(input)))
+    }
+
+    /// Read every statement of a program, e.g. the contents of a file
+    pub fn eval_program(input: &str) -> Result<Vec<Statement>, EvalError> {
+        let tokens =
+            tokenize(input).map_err(|err| EvalError::TokenizationError(err.render(input)))?;
+        parse_program(&tokens).map_err(|err| EvalError::ParsingError(err.render(input)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_program, Statement};
+    use crate::ast::AST;
+    use crate::encodings::build::{apply, lambda, var};
+    use crate::environment::Environment;
+    use crate::lexer::tokenize;
+
+    fn program(input: &str) -> Vec<Statement> {
+        parse_program(&tokenize(input).unwrap()).unwrap()
+    }
+
+    /// Define every definition of the program in order and resolve its expressions
+    fn run(input: &str) -> Vec<AST> {
+        let mut env = Environment::new();
+        let mut results = Vec::new();
+        for statement in program(input) {
+            match statement {
+                Statement::Definition(name, ast) => env = env.with(name, ast),
+                Statement::Expression(ast) => results.push(env.resolve(ast)),
+            }
+        }
+        results
+    }
+
+    #[test]
+    fn multiple_statements() {
+        let statements = program("id = $x -> x; k = $x, y -> x\nk id a;;");
+        match statements.as_slice() {
+            [Statement::Definition(id, id_term), Statement::Definition(k, k_term), Statement::Expression(ast)] =>
+            {
+                assert_eq!((id.as_str(), id_term), ("id", &lambda(&["x"], var("x"))));
+                assert_eq!((k.as_str(), k_term), ("k", &lambda(&["x", "y"], var("x"))));
+                assert_eq!(*ast, apply(var("k"), [var("id"), var("a")]));
+            }
+            statements => panic!(
+                "Expected two definitions and an expression, got {:?}",
+                statements
+            ),
+        }
+        // A statement on a line that does not start in the first column continues the previous one
+        assert_eq!(program("f\n  a").len(), 1);
+    }
+
+    #[test]
+    fn definitions_resolve() {
+        let results =
+            run("id = $x -> x\nk = $x, y -> x\nk id a\ntwice = $f, x -> f (f x)\ntwice k");
+        let k = lambda(&["x", "y"], var("x"));
+        let expected = [
+            apply(k.clone(), [lambda(&["x"], var("x")), var("a")]),
+            apply(
+                lambda(&["f", "x"], apply(var("f"), [apply(var("f"), [var("x")])])),
+                [k],
+            ),
+        ];
+        assert_eq!(results, expected);
+        // A redefinition does not change the terms defined before it
+        assert_eq!(
+            run("a = x; b = a; a = y; b a"),
+            [apply(var("x"), [var("y")])]
+        );
+    }
+
+    #[test]
+    fn let_binding() {
+        match program("let x = a in x x").as_slice() {
+            [Statement::Expression(ast)] => {
+                assert!(ast.is_let);
+                assert_eq!(
+                    *ast,
+                    apply(lambda(&["x"], apply(var("x"), [var("x")])), [var("a")])
+                );
+            }
+            statements => panic!("Expected an expression, got {:?}", statements),
+        }
+        // The body extends to the end of the statement, and the let can be defined
+        let results = run("f = let g = $x -> x in g g; f b");
+        let id = lambda(&["x"], var("x"));
+        let f = apply(lambda(&["g"], apply(var("g"), [var("g")])), [id]);
+        assert_eq!(results, [apply(f, [var("b")])]);
+    }
+}
@@ -0,0 +1,4 @@
+//! This module prints terms back in the surface syntax accepted by the parser
+pub mod printer;
+
+pub use self::printer::Printer;
@@ -0,0 +1,175 @@
+use crate::ast::Term;
+use crate::ast::AST;
+use std::fmt;
+
+/// Type represents the configuration of the printer
+///  - *collapse_abstractions*: print nested abstractions as one lambda with multiple parameters,
+///    i.e. $x -> $y -> x => $x, y -> x
+///  - *minimal_parens*: only emit the parentheses the parser needs to rebuild the same term
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Printer {
+    pub collapse_abstractions: bool,
+    pub minimal_parens: bool,
+}
+
+impl Default for Printer {
+    fn default() -> Self {
+        Printer {
+            collapse_abstractions: true,
+            minimal_parens: true,
+        }
+    }
+}
+
+impl Printer {
+    pub fn new() -> Self {
+        Printer::default()
+    }
+
+    /// Print the term so that parsing the output gives back an alpha-equivalent term
+    pub fn print(&self, ast: &AST) -> String {
+        let mut output = String::new();
+        self.write(ast, true, &mut output);
+        output
+    }
+
+    /// Recursively write a term. An abstraction extends as far to the right as possible,
+    /// so it only goes without parentheses if it is the rightmost part of the expression
+    fn write(&self, ast: &AST, rightmost: bool, output: &mut String) {
+        match &ast.term {
+            Term::Var(s) => output.push_str(s),
+            Term::Abstr(_, _) => {
+                let parens = !rightmost || !self.minimal_parens;
+                if parens {
+                    output.push('(');
+                }
+                let (params, body) = self.params(ast);
+                output.push('$');
+                output.push_str(&params.join(", "));
+                output.push_str(" -> ");
+                self.write(body, true, output);
+                if parens {
+                    output.push(')');
+                }
+            }
+            Term::Apply(f, arg) => {
+                if !self.minimal_parens {
+                    output.push('(');
+                }
+                // Application is left associative, so the left side never needs its own parentheses
+                self.write(f, false, output);
+                output.push(' ');
+                match arg.term {
+                    // An application on the right side has to be grouped
+                    Term::Apply(_, _) if self.minimal_parens => {
+                        output.push('(');
+                        self.write(arg, true, output);
+                        output.push(')');
+                    }
+                    _ => self.write(arg, rightmost || !self.minimal_parens, output),
+                }
+                if !self.minimal_parens {
+                    output.push(')');
+                }
+            }
+        }
+    }
+
+    /// Collect the parameters of an abstraction, including the nested ones if they are collapsed.
+    /// This is the inverse of the parser's desugaring of $x, y -> x => $x -> $y -> x
+    fn params<'a>(&self, ast: &'a AST) -> (Vec<String>, &'a AST) {
+        match &ast.term {
+            Term::Abstr(param, body) => {
+                let name = match &param.annotation {
+                    Some(ty) => format!("{}: {}", self.print(param), ty),
+                    None => self.print(param),
+                };
+                match (&body.term, self.collapse_abstractions) {
+                    (Term::Abstr(_, _), true) => {
+                        let (mut params, inner) = self.params(body);
+                        params.insert(0, name);
+                        (params, inner)
+                    }
+                    _ => (vec![name], body),
+                }
+            }
+            _ => (vec![], ast),
+        }
+    }
+}
+
+impl fmt::Display for AST {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Printer::new().print(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Printer;
+    use crate::ast::AST;
+
+    fn parse(input: &str) -> AST {
+        AST::eval(input).unwrap()
+    }
+
+    /// Every configuration of the printer
+    fn printers() -> Vec<Printer> {
+        let mut printers = Vec::new();
+        for collapse_abstractions in [true, false] {
+            for minimal_parens in [true, false] {
+                printers.push(Printer {
+                    collapse_abstractions,
+                    minimal_parens,
+                });
+            }
+        }
+        printers
+    }
+
+    #[test]
+    fn parse_print_round_trip() {
+        let terms = [
+            // Nested abstractions
+            "$x -> $y -> $z -> x z (y z)",
+            "$x, y -> $z -> x ($w -> w z) y",
+            "($f, x -> f x) ($y, z -> z y)",
+            // Left nested applications
+            "a b c d",
+            "(a b) c",
+            "($x -> x) a b",
+            // Right nested applications
+            "a (b (c d))",
+            "f (g ($x -> x)) h",
+            "a ($x -> x b) c",
+            // Shadowed names
+            "$x -> $x -> x",
+            "$x -> ($x -> x) x",
+            "($x, x -> x x) a b",
+            "$x -> x ($x -> $y -> x y) x",
+        ];
+        for printer in printers() {
+            for input in terms {
+                let ast = parse(input);
+                let printed = printer.print(&ast);
+                assert_eq!(parse(&printed), ast, "{:?} printed {}", printer, printed);
+            }
+        }
+    }
+
+    #[test]
+    fn configurations() {
+        let ast = parse("$x -> $y -> x (y x)");
+        let print = |collapse_abstractions, minimal_parens| {
+            let printer = Printer {
+                collapse_abstractions,
+                minimal_parens,
+            };
+            printer.print(&ast)
+        };
+        assert_eq!(print(true, true), "$x, y -> x (y x)");
+        assert_eq!(print(false, true), "$x -> $y -> x (y x)");
+        assert_eq!(print(true, false), "($x, y -> (x (y x)))");
+        assert_eq!(print(false, false), "($x -> ($y -> (x (y x))))");
+    }
+}
@@ -0,0 +1,574 @@
+use crate::alpha::AlphaVariant;
+use crate::ast::Term;
+use crate::ast::AST;
+use crate::proof::Prop;
+use crate::span::render_snippet;
+use crate::substitution::Substitution;
+use crate::types::Type;
+use std::fmt;
+
+/// Constants of the proof terms with the number of their arguments
+///  - *pair a b*: proves A & B from proofs of A and B
+///  - *fst p*, *snd p*: prove A and B from a proof of A & B
+///  - *inl a*, *inr b*: prove A | B from a proof of A or of B
+///  - *case d f g*: proves C from a proof of A | B and proofs of A -> C and B -> C
+///  - *absurd p*: proves anything from a proof of False
+const CONSTANTS: [(&str, usize); 7] = [
+    ("pair", 2),
+    ("fst", 1),
+    ("snd", 1),
+    ("inl", 1),
+    ("inr", 1),
+    ("case", 3),
+    ("absurd", 1),
+];
+
+/// Type represents the kinds of propositions a term has to prove
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Form {
+    Implication,
+    Conjunction,
+    Disjunction,
+}
+
+impl fmt::Display for Form {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Form::Implication => write!(f, "an implication"),
+            Form::Conjunction => write!(f, "a conjunction"),
+            Form::Disjunction => write!(f, "a disjunction"),
+        }
+    }
+}
+
+/// Type for errors of proof checking, each one carries the subterm that fails its subgoal
+///  - *UnboundVariable*: a variable that is neither a hypothesis nor a constant
+///  - *Arity*: a constant with fewer arguments than it needs
+///  - *NotInferable*: a term whose proposition cannot be found without a goal, e.g. inl a
+///  - *NotAProposition*: a parameter annotated with a type that is not a proposition
+///  - *Assumption*: a parameter annotated with a different proposition than the premise
+///  - *Mismatch*: a term proving a different proposition than its goal
+///  - *WrongForm*: a term proving a form of proposition its goal does not have, e.g. a pair
+///    for an implication
+///  - *NotA*: a term used as a proof of a form of proposition it does not prove, e.g. fst x
+///    where x is not a proof of a conjunction
+#[derive(Debug, Clone)]
+pub enum ProofError {
+    UnboundVariable(Box<AST>),
+    Arity {
+        term: Box<AST>,
+        constant: String,
+        arity: usize,
+    },
+    NotInferable(Box<AST>),
+    NotAProposition(Box<AST>, Type),
+    Assumption {
+        param: Box<AST>,
+        premise: Prop,
+        assumed: Prop,
+    },
+    Mismatch {
+        term: Box<AST>,
+        goal: Prop,
+        found: Prop,
+    },
+    WrongForm {
+        term: Box<AST>,
+        goal: Prop,
+        form: Form,
+    },
+    NotA {
+        term: Box<AST>,
+        found: Prop,
+        form: Form,
+    },
+}
+
+impl ProofError {
+    /// The subterm the error is about
+    pub fn term(&self) -> &AST {
+        match self {
+            ProofError::UnboundVariable(term)
+            | ProofError::Arity { term, .. }
+            | ProofError::NotInferable(term)
+            | ProofError::NotAProposition(term, _)
+            | ProofError::Assumption { param: term, .. }
+            | ProofError::Mismatch { term, .. }
+            | ProofError::WrongForm { term, .. }
+            | ProofError::NotA { term, .. } => term,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            ProofError::UnboundVariable(var) => {
+                format!("'{}' is neither a hypothesis nor a constant", var)
+            }
+            ProofError::Arity {
+                term,
+                constant,
+                arity,
+            } => format!(
+                "'{}' is not a proof, '{}' needs {} argument{}",
+                term,
+                constant,
+                arity,
+                if *arity == 1 { "" } else { "s" }
+            ),
+            ProofError::NotInferable(term) => format!(
+                "Cannot tell what '{}' proves here, it needs a known goal or annotations",
+                term
+            ),
+            ProofError::NotAProposition(param, ty) => {
+                format!("The type {} of '{}' is not a proposition", ty, param)
+            }
+            ProofError::Assumption {
+                param,
+                premise,
+                assumed,
+            } => format!(
+                "The parameter '{}' assumes {}, but the premise of the goal is {}",
+                param, assumed, premise
+            ),
+            ProofError::Mismatch { term, goal, found } => {
+                format!("'{}' proves {}, but the goal is {}", term, found, goal)
+            }
+            ProofError::WrongForm { term, goal, form } => {
+                format!("'{}' proves {}, but the goal is {}", term, form, goal)
+            }
+            ProofError::NotA { term, found, form } => {
+                format!("'{}' proves {}, which is not {}", term, found, form)
+            }
+        }
+    }
+
+    /// Render the error as an annotated snippet of the input if the subterm was parsed from it
+    pub fn render(&self, source: &str) -> String {
+        match self.term().span {
+            Some(span) => render_snippet(source, span, &self.message()),
+            None => format!("error: {}", self.message()),
+        }
+    }
+}
+
+pub trait Proof {
+    fn prove(&self, goal: &Prop) -> Result<(), ProofError>;
+}
+
+impl AST {
+    /// Split an application into its head and arguments, i.e. f a b => (f, [a, b])
+    fn spine(&self) -> (&AST, Vec<&AST>) {
+        match &self.term {
+            Term::Apply(f, arg) => {
+                let (head, mut args) = f.spine();
+                args.push(arg);
+                (head, args)
+            }
+            _ => (self, Vec::new()),
+        }
+    }
+
+    /// The constant at the head of the term with its arity and arguments. A hypothesis
+    /// of the same name hides the constant
+    fn constant(&self, context: &[(String, Prop)]) -> Option<(&'static str, usize, Vec<&AST>)> {
+        let (head, args) = self.spine();
+        let name = match &head.term {
+            Term::Var(s) if !context.iter().any(|(name, _)| name == s) => s,
+            _ => return None,
+        };
+        CONSTANTS
+            .iter()
+            .find(|(constant, _)| constant == name)
+            .map(|(constant, arity)| (*constant, *arity, args))
+    }
+
+    /// The proposition an annotated parameter is assumed to prove
+    fn assumption(param: &AST) -> Result<Option<Prop>, ProofError> {
+        match &param.annotation {
+            Some(ty) => match Prop::from_type(ty) {
+                Some(prop) => Ok(Some(prop)),
+                None => Err(ProofError::NotAProposition(
+                    Box::new(param.clone()),
+                    ty.clone(),
+                )),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Name of the hypothesis a parameter introduces
+    fn hypothesis(param: &AST) -> String {
+        match &param.term {
+            Term::Var(s) => s.clone(),
+            _ => panic!("Abstraction parameter must be a variable"),
+        }
+    }
+
+    /// Error for a term that proves a different form of proposition than the goal
+    fn wrong_form(&self, goal: &Prop, form: Form) -> ProofError {
+        ProofError::WrongForm {
+            term: Box::new(self.clone()),
+            goal: goal.clone(),
+            form,
+        }
+    }
+
+    /// Prove the lemma of a let binding and go on with its body. An annotated parameter states
+    /// the lemma, so the argument is checked against it, otherwise the lemma is inferred.
+    /// A lemma that cannot be inferred, e.g. from an injection, is proved where it is used
+    /// instead, by putting the argument in the place of the parameter
+    fn let_binding<T>(
+        param: &AST,
+        body: &AST,
+        arg: &AST,
+        context: &mut Vec<(String, Prop)>,
+        then: impl FnOnce(&AST, &mut Vec<(String, Prop)>) -> Result<T, ProofError>,
+    ) -> Result<T, ProofError> {
+        let lemma = match AST::assumption(param)? {
+            Some(lemma) => arg.check_proof(&lemma, context).map(|()| lemma),
+            None => arg.infer_proof(context),
+        };
+        match lemma {
+            Ok(lemma) => {
+                context.push((AST::hypothesis(param), lemma));
+                let result = then(body, context);
+                context.pop();
+                result
+            }
+            Err(ProofError::NotInferable(_)) => {
+                let body = body
+                    .clone()
+                    .substitute(param.term.clone(), arg.clone())
+                    .unwrap_or_else(|_| {
+                        body.alpha_variant()
+                            .substitute(param.term.clone(), arg.clone())
+                            .unwrap()
+                    });
+                then(&body, context)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Check the term is a proof of the goal with the hypotheses, the innermost last
+    fn check_proof(
+        &self,
+        goal: &Prop,
+        context: &mut Vec<(String, Prop)>,
+    ) -> Result<(), ProofError> {
+        match &self.term {
+            // A function proves an implication by proving the conclusion from the premise
+            Term::Abstr(param, body) => match goal {
+                Prop::Implies(premise, conclusion) => {
+                    if let Some(assumed) = AST::assumption(param)? {
+                        if assumed != **premise {
+                            return Err(ProofError::Assumption {
+                                param: param.clone(),
+                                premise: *premise.clone(),
+                                assumed,
+                            });
+                        }
+                    }
+                    context.push((AST::hypothesis(param), *premise.clone()));
+                    let result = body.check_proof(conclusion, context);
+                    context.pop();
+                    result
+                }
+                _ => Err(self.wrong_form(goal, Form::Implication)),
+            },
+            Term::Apply(f, arg) => match &f.term {
+                // A let binding proves a lemma first and uses it as a hypothesis
+                Term::Abstr(param, body) => {
+                    AST::let_binding(param, body, arg, context, |body, context| {
+                        body.check_proof(goal, context)
+                    })
+                }
+                _ => self.check_constant(goal, context),
+            },
+            Term::Var(_) => self.check_constant(goal, context),
+        }
+    }
+
+    /// Check a constant applied to its arguments against the goal. Introductions need the goal,
+    /// so their arguments are checked, anything else is inferred and compared with it
+    fn check_constant(
+        &self,
+        goal: &Prop,
+        context: &mut Vec<(String, Prop)>,
+    ) -> Result<(), ProofError> {
+        let (constant, args) = match self.constant(context) {
+            Some((constant, arity, args)) if args.len() == arity => (constant, args),
+            _ => return self.compare_proof(goal, context),
+        };
+        match (constant, goal) {
+            ("pair", Prop::And(left, right)) => {
+                args[0].check_proof(left, context)?;
+                args[1].check_proof(right, context)
+            }
+            ("pair", _) => Err(self.wrong_form(goal, Form::Conjunction)),
+            ("inl", Prop::Or(left, _)) => args[0].check_proof(left, context),
+            ("inr", Prop::Or(_, right)) => args[0].check_proof(right, context),
+            ("inl" | "inr", _) => Err(self.wrong_form(goal, Form::Disjunction)),
+            // Both cases have to prove the goal
+            ("case", _) => {
+                let (left, right) = args[0].infer_disjunction(context)?;
+                args[1].check_proof(&Prop::implies(left, goal.clone()), context)?;
+                args[2].check_proof(&Prop::implies(right, goal.clone()), context)
+            }
+            ("absurd", _) => args[0].check_proof(&Prop::False, context),
+            _ => self.compare_proof(goal, context),
+        }
+    }
+
+    /// Infer what the term proves and compare it with the goal
+    fn compare_proof(
+        &self,
+        goal: &Prop,
+        context: &mut Vec<(String, Prop)>,
+    ) -> Result<(), ProofError> {
+        let found = self.infer_proof(context)?;
+        match found == *goal {
+            true => Ok(()),
+            false => Err(ProofError::Mismatch {
+                term: Box::new(self.clone()),
+                goal: goal.clone(),
+                found,
+            }),
+        }
+    }
+
+    /// Infer the two sides of the disjunction the term proves
+    fn infer_disjunction(
+        &self,
+        context: &mut Vec<(String, Prop)>,
+    ) -> Result<(Prop, Prop), ProofError> {
+        match self.infer_proof(context)? {
+            Prop::Or(left, right) => Ok((*left, *right)),
+            found => Err(ProofError::NotA {
+                term: Box::new(self.clone()),
+                found,
+                form: Form::Disjunction,
+            }),
+        }
+    }
+
+    /// Infer the proposition the term proves with the hypotheses, the innermost last
+    fn infer_proof(&self, context: &mut Vec<(String, Prop)>) -> Result<Prop, ProofError> {
+        if let Some((constant, arity, args)) = self.constant(context) {
+            if args.len() < arity {
+                return Err(ProofError::Arity {
+                    term: Box::new(self.clone()),
+                    constant: constant.to_string(),
+                    arity,
+                });
+            }
+            if args.len() == arity {
+                return self.infer_constant(constant, &args, context);
+            }
+        }
+        match &self.term {
+            Term::Var(s) => context
+                .iter()
+                .rev()
+                .find(|(name, _)| name == s)
+                .map(|(_, prop)| prop.clone())
+                .ok_or_else(|| ProofError::UnboundVariable(Box::new(self.clone()))),
+            // Only an annotated premise is known without a goal
+            Term::Abstr(param, body) => match AST::assumption(param)? {
+                Some(premise) => {
+                    context.push((AST::hypothesis(param), premise.clone()));
+                    let conclusion = body.infer_proof(context);
+                    context.pop();
+                    Ok(Prop::implies(premise, conclusion?))
+                }
+                None => Err(ProofError::NotInferable(Box::new(self.clone()))),
+            },
+            Term::Apply(f, arg) => match &f.term {
+                Term::Abstr(param, body) => {
+                    AST::let_binding(param, body, arg, context, |body, context| {
+                        body.infer_proof(context)
+                    })
+                }
+                // Modus ponens, the argument proves the premise of the implication
+                _ => match f.infer_proof(context)? {
+                    Prop::Implies(premise, conclusion) => {
+                        arg.check_proof(&premise, context)?;
+                        Ok(*conclusion)
+                    }
+                    found => Err(ProofError::NotA {
+                        term: f.clone(),
+                        found,
+                        form: Form::Implication,
+                    }),
+                },
+            },
+        }
+    }
+
+    /// Infer the proposition a constant proves when applied to its arguments
+    fn infer_constant(
+        &self,
+        constant: &str,
+        args: &[&AST],
+        context: &mut Vec<(String, Prop)>,
+    ) -> Result<Prop, ProofError> {
+        let not_a_conjunction = |term: &AST, found| ProofError::NotA {
+            term: Box::new(term.clone()),
+            found,
+            form: Form::Conjunction,
+        };
+        match constant {
+            "pair" => Ok(Prop::and(
+                args[0].infer_proof(context)?,
+                args[1].infer_proof(context)?,
+            )),
+            "fst" => match args[0].infer_proof(context)? {
+                Prop::And(left, _) => Ok(*left),
+                found => Err(not_a_conjunction(args[0], found)),
+            },
+            "snd" => match args[0].infer_proof(context)? {
+                Prop::And(_, right) => Ok(*right),
+                found => Err(not_a_conjunction(args[0], found)),
+            },
+            // The conclusion comes from the first case, the second one has to agree
+            "case" => {
+                let (left, right) = args[0].infer_disjunction(context)?;
+                let conclusion = match args[1].infer_proof(context)? {
+                    Prop::Implies(premise, conclusion) if *premise == left => *conclusion,
+                    Prop::Implies(premise, conclusion) => {
+                        return Err(ProofError::Mismatch {
+                            term: Box::new(args[1].clone()),
+                            goal: Prop::implies(left, *conclusion.clone()),
+                            found: Prop::Implies(premise, conclusion),
+                        })
+                    }
+                    found => {
+                        return Err(ProofError::NotA {
+                            term: Box::new(args[1].clone()),
+                            found,
+                            form: Form::Implication,
+                        })
+                    }
+                };
+                args[2].check_proof(&Prop::implies(right, conclusion.clone()), context)?;
+                Ok(conclusion)
+            }
+            // The other side of a disjunction and the conclusion from falsity are not known
+            _ => Err(ProofError::NotInferable(Box::new(self.clone()))),
+        }
+    }
+}
+
+impl Proof for AST {
+    /// Check the closed term is a proof of the proposition, i.e. has the proposition as its type
+    fn prove(&self, goal: &Prop) -> Result<(), ProofError> {
+        self.check_proof(goal, &mut Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Form, Proof, ProofError};
+    use crate::ast::AST;
+    use crate::proof::Prop;
+
+    fn prove(prop: &str, term: &str) -> Result<(), ProofError> {
+        AST::eval(term).unwrap().prove(&Prop::eval(prop).unwrap())
+    }
+
+    #[test]
+    fn proofs() {
+        assert!(prove("A -> A", "$x -> x").is_ok());
+        assert!(prove("A & B -> B & A", "$p -> pair (snd p) (fst p)").is_ok());
+        assert!(prove("A | B -> B | A", "$d -> case d ($a -> inr a) ($b -> inl b)").is_ok());
+        assert!(prove("False -> A", "$f -> absurd f").is_ok());
+    }
+
+    #[test]
+    fn let_bindings() {
+        // The lemma cannot be inferred, so it is proved where it is used
+        assert!(prove("A -> A | B", "let f = $x: A -> inl x in f").is_ok());
+        // The annotation states the lemma, so the argument needs no annotations of its own
+        assert!(prove("A -> A", "($f: A -> A -> f) ($x -> x)").is_ok());
+        assert!(matches!(
+            prove("A -> A", "($f: A -> B -> f) ($x -> x)"),
+            Err(ProofError::Mismatch { .. })
+        ));
+        assert!(matches!(
+            prove("B -> B", "($f: A -> A -> f) ($x -> x)"),
+            Err(ProofError::Mismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn assumption() {
+        assert!(matches!(
+            prove("A -> A", "$x: B -> x"),
+            Err(ProofError::Assumption { .. })
+        ));
+    }
+
+    #[test]
+    fn wrong_form() {
+        assert!(matches!(
+            prove("A -> A", "pair a b"),
+            Err(ProofError::WrongForm {
+                form: Form::Conjunction,
+                ..
+            })
+        ));
+        assert!(matches!(
+            prove("A & B", "$x -> x"),
+            Err(ProofError::WrongForm {
+                form: Form::Implication,
+                ..
+            })
+        ));
+        assert!(matches!(
+            prove("A & B -> A", "$p -> inl p"),
+            Err(ProofError::WrongForm {
+                form: Form::Disjunction,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn not_a() {
+        assert!(matches!(
+            prove("A -> A", "$x -> fst x"),
+            Err(ProofError::NotA {
+                form: Form::Conjunction,
+                ..
+            })
+        ));
+        assert!(matches!(
+            prove("A -> B -> B", "$x, y -> x y"),
+            Err(ProofError::NotA {
+                form: Form::Implication,
+                ..
+            })
+        ));
+        assert!(matches!(
+            prove("A -> A", "$x -> case x ($a -> a) ($b -> b)"),
+            Err(ProofError::NotA {
+                form: Form::Disjunction,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn arity() {
+        let err = prove("A -> A & A", "$x -> (pair x) (fst)").unwrap_err();
+        assert!(matches!(err, ProofError::Arity { arity: 1, .. }));
+        assert_eq!(
+            err.message(),
+            "'fst' is not a proof, 'fst' needs 1 argument"
+        );
+        assert!(matches!(
+            prove("A -> A & A", "$x -> (pair x)"),
+            Err(ProofError::Arity { arity: 2, .. })
+        ));
+    }
+}
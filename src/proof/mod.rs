@@ -0,0 +1,6 @@
+//! This module handles propositions and checking lambda terms as their proofs
+pub mod checker;
+pub mod prop;
+
+pub use self::checker::Proof;
+pub use self::prop::Prop;
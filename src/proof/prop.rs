@@ -0,0 +1,194 @@
+use crate::ast::ast::EvalError;
+use crate::lexer::{tokenize, Token, TokenKind};
+use crate::parser::parser::trailing_token;
+use crate::parser::ParsingError;
+use crate::span::Span;
+use crate::types::Type;
+use std::fmt;
+
+/// Type represents a formula of propositional logic, which is proven by the terms of its type
+///  - *Atom*: a proposition given by its name, e.g. A
+///  - *Implies*: an implication, proven by a function from proofs to proofs, e.g. A -> B
+///  - *And*: a conjunction, proven by a pair of proofs, e.g. A & B
+///  - *Or*: a disjunction, proven by a proof of either side, e.g. A | B
+///  - *False*: the proposition without proofs, written False or ⊥
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Prop {
+    Atom(String),
+    Implies(Box<Prop>, Box<Prop>),
+    And(Box<Prop>, Box<Prop>),
+    Or(Box<Prop>, Box<Prop>),
+    False,
+}
+
+impl Prop {
+    pub fn implies(premise: Prop, conclusion: Prop) -> Prop {
+        Prop::Implies(Box::new(premise), Box::new(conclusion))
+    }
+
+    pub fn and(left: Prop, right: Prop) -> Prop {
+        Prop::And(Box::new(left), Box::new(right))
+    }
+
+    pub fn or(left: Prop, right: Prop) -> Prop {
+        Prop::Or(Box::new(left), Box::new(right))
+    }
+
+    /// Read the type of an annotated parameter as a proposition, the type False is falsity
+    pub fn from_type(ty: &Type) -> Option<Prop> {
+        match ty {
+            Type::Base(name) if name == "False" => Some(Prop::False),
+            Type::Base(name) => Some(Prop::Atom(name.clone())),
+            Type::Arrow(from, to) => {
+                Some(Prop::implies(Prop::from_type(from)?, Prop::from_type(to)?))
+            }
+            Type::Var(_) | Type::Forall(_, _) => None,
+        }
+    }
+
+    pub fn eval(input: &str) -> Result<Prop, EvalError> {
+        let tokens =
+            tokenize(input).map_err(|err| EvalError::TokenizationError(err.render(input)))?;
+        parse_prop(&tokens).map_err(|err| EvalError::ParsingError(err.render(input)))
+    }
+
+    /// How tightly the connective binds, a part binding less tightly has to be grouped
+    fn precedence(&self) -> usize {
+        match self {
+            Prop::Implies(_, _) => 0,
+            Prop::Or(_, _) => 1,
+            Prop::And(_, _) => 2,
+            Prop::Atom(_) | Prop::False => 3,
+        }
+    }
+
+    /// Write a side of a connective, in parentheses if it binds less tightly than the connective
+    fn write_part(&self, precedence: usize, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.precedence() < precedence {
+            true => write!(f, "({})", self),
+            false => write!(f, "{}", self),
+        }
+    }
+
+    /// Write a connective, which associates to the right, i.e. A & B & C => A & (B & C)
+    fn write_binary(
+        &self,
+        left: &Prop,
+        symbol: &str,
+        right: &Prop,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        left.write_part(self.precedence() + 1, f)?;
+        write!(f, " {} ", symbol)?;
+        right.write_part(self.precedence(), f)
+    }
+}
+
+impl fmt::Display for Prop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Prop::Atom(name) => write!(f, "{}", name),
+            Prop::False => write!(f, "False"),
+            Prop::Implies(left, right) => self.write_binary(left, "->", right, f),
+            Prop::Or(left, right) => self.write_binary(left, "|", right, f),
+            Prop::And(left, right) => self.write_binary(left, "&", right, f),
+        }
+    }
+}
+
+/// Parser of an atom or a proposition in parentheses
+fn atom(tokens: &[Token]) -> Result<(Prop, &[Token]), ParsingError> {
+    match tokens {
+        [Token {
+            kind: TokenKind::Var(name),
+            ..
+        }, rest @ ..]
+            if name == "False" =>
+        {
+            Ok((Prop::False, rest))
+        }
+        [Token {
+            kind: TokenKind::Bottom,
+            ..
+        }, rest @ ..] => Ok((Prop::False, rest)),
+        [Token {
+            kind: TokenKind::Var(name),
+            ..
+        }, rest @ ..] => Ok((Prop::Atom(name.clone()), rest)),
+        [Token {
+            kind: TokenKind::LParen,
+            span,
+        }, rest @ ..] => {
+            let (prop, rest) = implication(rest)?;
+            match rest {
+                [Token {
+                    kind: TokenKind::RParen,
+                    ..
+                }, rest @ ..] => Ok((prop, rest)),
+                [token, ..] if token.kind != TokenKind::Eof => Err(ParsingError::MismatchedParens(
+                    format!("Expected ')' but found {}", token.kind),
+                    token.span,
+                )),
+                _ => Err(ParsingError::MismatchedParens(
+                    "Unclosed '(' at the end of input".to_string(),
+                    *span,
+                )),
+            }
+        }
+        [token, ..] => Err(ParsingError::InvalidSyntax(
+            format!("Expected a proposition, found {}", token.kind),
+            token.span,
+        )),
+        [] => Err(ParsingError::InvalidSyntax(
+            "Unexpected end of input when reading a proposition".to_string(),
+            Span::default(),
+        )),
+    }
+}
+
+/// Type of the parsers of propositions, which leave the tokens after the proposition in the rest
+type PropParser = fn(&[Token]) -> Result<(Prop, &[Token]), ParsingError>;
+
+/// Parser of a connective, whose sides are read by the given parser
+fn connective(
+    tokens: &[Token],
+    kind: TokenKind,
+    part: PropParser,
+    build: fn(Prop, Prop) -> Prop,
+) -> Result<(Prop, &[Token]), ParsingError> {
+    let (left, rest) = part(tokens)?;
+    match rest {
+        [token, rest @ ..] if token.kind == kind => {
+            let (right, rest) = connective(rest, kind, part, build)?;
+            Ok((build(left, right), rest))
+        }
+        _ => Ok((left, rest)),
+    }
+}
+
+/// Conjunction binds the most tightly of the connectives
+fn conjunction(tokens: &[Token]) -> Result<(Prop, &[Token]), ParsingError> {
+    connective(tokens, TokenKind::And, atom, Prop::and)
+}
+
+fn disjunction(tokens: &[Token]) -> Result<(Prop, &[Token]), ParsingError> {
+    connective(tokens, TokenKind::Or, conjunction, Prop::or)
+}
+
+/// Implication binds the least tightly, i.e. A & B -> A | B => (A & B) -> (A | B)
+fn implication(tokens: &[Token]) -> Result<(Prop, &[Token]), ParsingError> {
+    connective(tokens, TokenKind::Arrow, disjunction, Prop::implies)
+}
+
+/// Parse a single proposition and check that the input is finished
+pub fn parse_prop(tokens: &[Token]) -> Result<Prop, ParsingError> {
+    let (prop, rest) = implication(tokens)?;
+    match rest {
+        []
+        | [Token {
+            kind: TokenKind::Eof,
+            ..
+        }, ..] => Ok(prop),
+        [token, ..] => Err(trailing_token(token)),
+    }
+}
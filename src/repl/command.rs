@@ -0,0 +1,236 @@
+use crate::backend::Backend;
+use crate::beta::EvalOrder;
+use crate::encodings::EncodingKind;
+use std::time::Duration;
+
+/// Type represents a single line of input to the REPL
+///  - *Eval*: a term to reduce
+///  - *Order*: show or set the evaluation order
+///  - *Backend*: show or set the evaluator
+///  - *Trace*: turn printing of every reduction step on or off
+///  - *Eta*: turn eta reduction of the normal forms on or off
+///  - *Decode*: turn printing of the values the normal forms encode on or off
+///  - *Encoding*: show or set the encoding the numbers and lists are decoded from
+///  - *Collapse*: turn printing of nested abstractions as one with several parameters on or off
+///  - *MinimalParens*: turn printing of only the parentheses the parser needs on or off
+///  - *Steps*: show or set the step budget, *None* means unlimited
+///  - *Timeout*: show or set the time limit of every reduction, *None* means unlimited
+///  - *Whnf*, *Hnf*: reduce a term only to its weak head or head normal form
+///  - *Need*: reduce a term by call-by-need and show how many times each argument was forced
+///  - *Vm*: compile a term to bytecode, run it and show the code with the counters of the run
+///  - *Type*: show the most general type of a term
+///  - *Check*: check a term with annotated parameters in the simply typed lambda calculus
+///  - *SystemF*: check a System F term, then erase its types and reduce it
+///  - *Prove*: check a term is a proof of a proposition, both given as their source
+///  - *Ski*: translate a term into combinators by every bracket abstraction and compare the sizes
+///  - *Alpha*: check whether two terms are alpha-equivalent, both given as their source
+///  - *Equivalent*: check whether two terms are beta-eta-equivalent, both given as their source
+///  - *Expand*: eta expand a term once
+///  - *Definitions*: list the definitions made so far
+///  - *Prelude*: add the definitions of the prelude
+///  - *Load*: run the statements of a file
+///  - *History*: list the previous inputs
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Eval(String),
+    Order(Option<EvalOrder>),
+    Backend(Option<Backend>),
+    Trace(bool),
+    Eta(bool),
+    Decode(bool),
+    Encoding(Option<EncodingKind>),
+    Collapse(bool),
+    MinimalParens(bool),
+    Steps(Option<usize>),
+    ShowSteps,
+    Timeout(Option<Duration>),
+    ShowTimeout,
+    Whnf(String),
+    Need(String),
+    Vm(String),
+    Hnf(String),
+    Type(String),
+    Check(String),
+    SystemF(String),
+    Prove(String, String),
+    Ski(String),
+    Alpha(String, String),
+    Equivalent(String, String),
+    Expand(String),
+    Definitions,
+    Prelude,
+    Load(String),
+    History,
+    Help,
+    Quit,
+}
+
+/// Parse an on/off switch
+fn switch(arg: &str) -> Result<bool, String> {
+    match arg {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        _ => Err(format!("Expected 'on' or 'off', found '{}'", arg)),
+    }
+}
+
+/// Split the argument of a command comparing two terms at the separator
+fn two_terms(name: &str, arg: &str) -> Result<(String, String), String> {
+    match arg.split_once("==") {
+        Some((left, right)) => Ok((left.trim().to_string(), right.trim().to_string())),
+        None => Err(format!(
+            "Expected two terms, i.e. :{} $x -> x == $y -> y",
+            name
+        )),
+    }
+}
+
+impl Command {
+    /// Parse a line of input. Lines starting with ':' are meta-commands, anything else is
+    /// a term or a definition
+    pub fn parse(line: &str) -> Result<Command, String> {
+        let line = line.trim();
+        let (name, arg) = match line.strip_prefix(':') {
+            None => return Ok(Command::Eval(line.to_string())),
+            Some(command) => match command.split_once(char::is_whitespace) {
+                Some((name, arg)) => (name, arg.trim()),
+                None => (command, ""),
+            },
+        };
+        match (name, arg) {
+            ("order", "") => Ok(Command::Order(None)),
+            ("order", order) => Ok(Command::Order(Some(order.parse()?))),
+            ("backend", "") => Ok(Command::Backend(None)),
+            ("backend", backend) => Ok(Command::Backend(Some(backend.parse()?))),
+            ("trace", arg) => Ok(Command::Trace(switch(arg)?)),
+            ("eta", arg) => Ok(Command::Eta(switch(arg)?)),
+            ("decode", arg) => Ok(Command::Decode(switch(arg)?)),
+            ("collapse", arg) => Ok(Command::Collapse(switch(arg)?)),
+            ("minparens", arg) => Ok(Command::MinimalParens(switch(arg)?)),
+            ("encoding", "") => Ok(Command::Encoding(None)),
+            ("encoding", encoding) => Ok(Command::Encoding(Some(encoding.parse()?))),
+            ("steps", "") => Ok(Command::ShowSteps),
+            ("steps", "off") => Ok(Command::Steps(None)),
+            ("steps", n) => match n.parse() {
+                Ok(n) => Ok(Command::Steps(Some(n))),
+                Err(_) => Err(format!(
+                    "Expected a number of steps or 'off', found '{}'",
+                    n
+                )),
+            },
+            ("timeout", "") => Ok(Command::ShowTimeout),
+            ("timeout", "off") => Ok(Command::Timeout(None)),
+            ("timeout", seconds) => match seconds.parse() {
+                Ok(seconds) => Ok(Command::Timeout(Some(Duration::from_secs(seconds)))),
+                Err(_) => Err(format!(
+                    "Expected a number of seconds or 'off', found '{}'",
+                    seconds
+                )),
+            },
+            ("need", term) => Ok(Command::Need(term.to_string())),
+            ("vm", term) => Ok(Command::Vm(term.to_string())),
+            ("whnf", term) => Ok(Command::Whnf(term.to_string())),
+            ("hnf", term) => Ok(Command::Hnf(term.to_string())),
+            ("type", term) => Ok(Command::Type(term.to_string())),
+            ("check", term) => Ok(Command::Check(term.to_string())),
+            ("systemf", term) => Ok(Command::SystemF(term.to_string())),
+            ("ski", term) => Ok(Command::Ski(term.to_string())),
+            ("prove", arg) => match arg.split_once(":=") {
+                Some((prop, term)) => Ok(Command::Prove(
+                    prop.trim().to_string(),
+                    term.trim().to_string(),
+                )),
+                None => Err(
+                    "Expected a proposition and its proof, i.e. :prove A -> A := $x -> x"
+                        .to_string(),
+                ),
+            },
+            ("alpha", arg) => {
+                let (left, right) = two_terms(name, arg)?;
+                Ok(Command::Alpha(left, right))
+            }
+            ("eq", arg) => {
+                let (left, right) = two_terms(name, arg)?;
+                Ok(Command::Equivalent(left, right))
+            }
+            ("expand", term) => Ok(Command::Expand(term.to_string())),
+            ("defs", _) => Ok(Command::Definitions),
+            ("prelude", _) => Ok(Command::Prelude),
+            ("load", "") => Err("Expected a file to load".to_string()),
+            ("load", path) => Ok(Command::Load(path.to_string())),
+            ("history", _) => Ok(Command::History),
+            ("help", _) | ("h", _) | ("?", _) => Ok(Command::Help),
+            ("quit", _) | ("q", _) => Ok(Command::Quit),
+            _ => Err(format!("Unknown command ':{}', try :help", name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Command;
+    use crate::encodings::EncodingKind;
+    use std::time::Duration;
+
+    #[test]
+    fn alpha_splits_at_separator() {
+        assert_eq!(
+            Command::parse(":alpha $x -> x == $y -> y"),
+            Ok(Command::Alpha("$x -> x".to_string(), "$y -> y".to_string()))
+        );
+        assert_eq!(
+            Command::parse(":alpha a b == c"),
+            Ok(Command::Alpha("a b".to_string(), "c".to_string()))
+        );
+    }
+
+    #[test]
+    fn eq_splits_at_separator() {
+        assert_eq!(
+            Command::parse(":eq $x -> f x == f"),
+            Ok(Command::Equivalent(
+                "$x -> f x".to_string(),
+                "f".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn alpha_without_separator() {
+        assert!(Command::parse(":alpha ($x -> x) ($y -> y)").is_err());
+    }
+
+    #[test]
+    fn encoding() {
+        assert_eq!(Command::parse(":encoding"), Ok(Command::Encoding(None)));
+        assert_eq!(
+            Command::parse(":encoding parigot"),
+            Ok(Command::Encoding(Some(EncodingKind::Parigot)))
+        );
+        assert!(Command::parse(":encoding binary").is_err());
+    }
+
+    #[test]
+    fn timeout() {
+        assert_eq!(Command::parse(":timeout"), Ok(Command::ShowTimeout));
+        assert_eq!(
+            Command::parse(":timeout 5"),
+            Ok(Command::Timeout(Some(Duration::from_secs(5))))
+        );
+        assert_eq!(Command::parse(":timeout off"), Ok(Command::Timeout(None)));
+        assert!(Command::parse(":timeout soon").is_err());
+    }
+
+    #[test]
+    fn printer_switches() {
+        assert_eq!(
+            Command::parse(":collapse off"),
+            Ok(Command::Collapse(false))
+        );
+        assert_eq!(
+            Command::parse(":minparens on"),
+            Ok(Command::MinimalParens(true))
+        );
+        assert!(Command::parse(":collapse maybe").is_err());
+    }
+}
@@ -0,0 +1,6 @@
+//! Interactive read-eval-print loop over the reduction engine
+pub mod command;
+pub mod repl;
+
+pub use self::command::Command;
+pub use self::repl::Repl;
@@ -0,0 +1,405 @@
+use crate::ast::AST;
+use crate::backend::Backend;
+use crate::beta::{BetaReduction, EvalOrder, ReductionLimits, ReductionResult};
+use crate::encodings::EncodingKind;
+use crate::environment::Environment;
+use crate::eta::EtaReduction;
+use crate::need::CallByNeed;
+use crate::parser::Statement;
+use crate::printer::Printer;
+use crate::proof::{Proof, Prop};
+use crate::repl::Command;
+use crate::ski::BracketAlgorithm;
+use crate::systemf::FTerm;
+use crate::types::{TypeCheck, TypeInference};
+use crate::vm::{Program, Vm};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::time::{Duration, Instant};
+
+const HELP: &str = "\
+Enter a term to reduce it to its normal form, e.g. ($x, y -> x) a b
+Define a name for later use with name = term, e.g. id = $x -> x
+Commands:
+  :order [normal|applicative|lazy]  show or set the evaluation order
+  :backend [tree|krivine|cek|nbe|vm]
+                                    show or set the evaluator, the machines stop at the weak head
+  :trace on|off                     print every reduction step
+  :eta on|off                       also eta reduce the normal forms
+  :decode on|off                    print the numbers, booleans, pairs and lists the results encode
+  :encoding [church|scott|parigot]  show or set the encoding of the decoded numbers and lists
+  :collapse on|off                  print nested lambdas as one, i.e. $x, y -> x
+  :minparens on|off                 print only the parentheses that are needed
+  :steps [n|off]                    show or set the maximum number of steps
+  :timeout [seconds|off]            show or set the time limit of every reduction
+  :need <term>                      reduce by call-by-need and count forced arguments
+  :vm <term>                        run the bytecode of a term and show the code and counters
+  :whnf <term>                      reduce a term to its weak head normal form
+  :hnf <term>                       reduce a term to its head normal form
+  :type <term>                      infer the most general type of a term, e.g. :type $x -> x
+  :check <term>                     check the type of a term whose parameters are annotated,
+                                    e.g. :check $f: A -> B, x: A -> f x
+  :systemf <term>                   check a System F term, then reduce it without the types,
+                                    e.g. :systemf (/\\a. $x: a -> x) [A]
+  :prove <prop> := <term>           check a term is a proof of a proposition with ->, &, | and
+                                    False, e.g. :prove A & B -> B & A := $p -> pair (snd p) (fst p)
+  :ski <term>                       translate a term into S, K, I, B, C and W combinators by
+                                    the naive and Turner's bracket abstraction, and check they
+                                    are beta-eta-equivalent to the term
+  :alpha <t1> == <t2>               check whether two terms are alpha-equivalent,
+                                    e.g. :alpha $x -> x == $y -> y
+  :eq <t1> == <t2>                  check whether two terms are beta-eta-equivalent,
+                                    e.g. :eq ($x -> x) f == $y -> f y
+  :expand <term>                    eta expand a term, e.g. :expand f
+  :defs                             list the definitions
+  :prelude                          define booleans, numerals, pairs and lists
+  :load <file>                      run the definitions and expressions of a file
+  :history                          list previous inputs
+  :help                             show this message
+  :quit                             exit";
+
+/// The longest the round trip of the translation into combinators is checked for
+const SKI_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Type represents the state of an interactive session
+pub struct Repl {
+    order: EvalOrder,
+    backend: Backend,
+    trace: bool,
+    eta: bool,
+    decode: bool,
+    encoding: EncodingKind,
+    printer: Printer,
+    fuel: Option<usize>,
+    timeout: Option<Duration>,
+    history: Vec<String>,
+    env: Environment,
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Repl {
+            order: EvalOrder::Normal,
+            backend: Backend::Tree,
+            trace: false,
+            eta: false,
+            decode: false,
+            encoding: EncodingKind::Church,
+            printer: Printer::default(),
+            fuel: Some(10_000),
+            timeout: Some(Duration::from_secs(10)),
+            history: Vec::new(),
+            env: Environment::new(),
+        }
+    }
+}
+
+impl Repl {
+    pub fn new() -> Self {
+        Repl::default()
+    }
+
+    /// Read lines from the standard input until it ends or the user quits
+    pub fn run(&mut self) -> io::Result<()> {
+        let stdin = io::stdin();
+        let mut lines = stdin.lock().lines();
+        loop {
+            print!("λ> ");
+            io::stdout().flush()?;
+            let line = match lines.next() {
+                Some(line) => line?,
+                None => return Ok(()),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            self.history.push(line.clone());
+            match Command::parse(&line) {
+                Ok(Command::Quit) => return Ok(()),
+                Ok(command) => self.execute(command),
+                Err(message) => println!("{}", message),
+            }
+        }
+    }
+
+    /// Execute a single command and print its output
+    pub fn execute(&mut self, command: Command) {
+        match command {
+            Command::Eval(input) => match Statement::eval(&input) {
+                Ok(statement) => self.statement(statement),
+                Err(err) => println!("{}", err),
+            },
+            Command::Order(None) => println!("Evaluation order: {}", self.order),
+            Command::Order(Some(order)) => self.order = order,
+            Command::Backend(None) => println!("Backend: {}", self.backend),
+            Command::Backend(Some(backend)) => self.backend = backend,
+            Command::Trace(trace) => self.trace = trace,
+            Command::Eta(eta) => self.eta = eta,
+            Command::Decode(decode) => self.decode = decode,
+            Command::Encoding(None) => println!("Encoding: {}", self.encoding),
+            Command::Encoding(Some(encoding)) => self.encoding = encoding,
+            Command::Collapse(collapse) => self.printer.collapse_abstractions = collapse,
+            Command::MinimalParens(minimal) => self.printer.minimal_parens = minimal,
+            Command::ShowSteps => match self.fuel {
+                Some(fuel) => println!("Maximum steps: {}", fuel),
+                None => println!("Maximum steps: unlimited"),
+            },
+            Command::Steps(fuel) => self.fuel = fuel,
+            Command::ShowTimeout => match self.timeout {
+                Some(timeout) => println!("Time limit: {} seconds", timeout.as_secs()),
+                None => println!("Time limit: unlimited"),
+            },
+            Command::Timeout(timeout) => self.timeout = timeout,
+            Command::Need(input) => match AST::eval(&input) {
+                Ok(ast) => self.need(self.env.resolve(ast)),
+                Err(err) => println!("{}", err),
+            },
+            Command::Vm(input) => match AST::eval(&input) {
+                Ok(ast) => self.vm(self.env.resolve(ast)),
+                Err(err) => println!("{}", err),
+            },
+            Command::Whnf(input) => match AST::eval(&input) {
+                Ok(ast) => {
+                    let result = self.env.resolve(ast).reduce_to_whnf(&self.limits());
+                    println!("{}", result.show(&self.printer))
+                }
+                Err(err) => println!("{}", err),
+            },
+            Command::Hnf(input) => match AST::eval(&input) {
+                Ok(ast) => {
+                    let result = self.env.resolve(ast).reduce_to_hnf(&self.limits());
+                    println!("{}", result.show(&self.printer))
+                }
+                Err(err) => println!("{}", err),
+            },
+            Command::Type(input) => match AST::eval(&input) {
+                Ok(ast) => match self.env.resolve(ast).infer_type() {
+                    Ok(ty) => println!("{}", ty),
+                    Err(err) => println!("{}", err.render(&input)),
+                },
+                Err(err) => println!("{}", err),
+            },
+            Command::Check(input) => match AST::eval(&input) {
+                Ok(ast) => match self.env.resolve(ast).type_of() {
+                    Ok(ty) => println!("{}", ty),
+                    Err(err) => println!("{}", err.render(&input)),
+                },
+                Err(err) => println!("{}", err),
+            },
+            Command::SystemF(input) => match FTerm::eval(&input) {
+                // Only a checked term is reduced, so the result has the type of the term
+                Ok(term) => match term.type_of() {
+                    Ok(ty) => {
+                        println!("{} : {}", term, ty);
+                        self.evaluate(term.erase());
+                    }
+                    Err(err) => println!("{}", err.render(&input)),
+                },
+                Err(err) => println!("{}", err),
+            },
+            Command::Prove(prop, input) => match (Prop::eval(&prop), AST::eval(&input)) {
+                (Ok(prop), Ok(ast)) => match ast.prove(&prop) {
+                    Ok(()) => println!("'{}' is a proof of {}", ast, prop),
+                    Err(err) => println!("{}", err.render(&input)),
+                },
+                (Err(err), _) | (_, Err(err)) => println!("{}", err),
+            },
+            Command::Ski(input) => match AST::eval(&input) {
+                Ok(ast) => self.ski(self.env.resolve(ast)),
+                Err(err) => println!("{}", err),
+            },
+            Command::Alpha(left, right) => match (AST::eval(&left), AST::eval(&right)) {
+                (Ok(left), Ok(right)) => {
+                    println!("{}", self.env.resolve(left) == self.env.resolve(right))
+                }
+                (Err(err), _) | (_, Err(err)) => println!("{}", err),
+            },
+            Command::Equivalent(left, right) => match (AST::eval(&left), AST::eval(&right)) {
+                (Ok(left), Ok(right)) => {
+                    let (left, right) = (self.env.resolve(left), self.env.resolve(right));
+                    match left.beta_eta_equivalent(&right, &self.limits()) {
+                        Some(equivalent) => println!("{}", equivalent),
+                        None => {
+                            println!("Cannot tell, a term has no normal form within the limits")
+                        }
+                    }
+                }
+                (Err(err), _) | (_, Err(err)) => println!("{}", err),
+            },
+            Command::Expand(input) => match AST::eval(&input) {
+                Ok(ast) => println!(
+                    "{}",
+                    self.printer.print(&self.env.resolve(ast).eta_expand())
+                ),
+                Err(err) => println!("{}", err),
+            },
+            Command::Definitions => {
+                for (name, ast) in self.env.definitions() {
+                    println!("{} = {}", name, self.printer.print(ast));
+                }
+            }
+            Command::Prelude => {
+                for (name, ast) in Environment::prelude().definitions() {
+                    self.env = std::mem::take(&mut self.env).with(name.clone(), ast.clone());
+                }
+            }
+            Command::Load(path) => match fs::read_to_string(&path) {
+                Ok(source) => match Statement::eval_program(&source) {
+                    Ok(statements) => {
+                        for statement in statements {
+                            self.statement(statement);
+                        }
+                    }
+                    Err(err) => println!("{}: {}", path, err),
+                },
+                Err(err) => println!("Cannot read '{}': {}", path, err),
+            },
+            Command::History => {
+                for (i, line) in self.history.iter().enumerate() {
+                    println!("{:>4}  {}", i + 1, line);
+                }
+            }
+            Command::Help => println!("{}", HELP),
+            Command::Quit => {}
+        }
+    }
+
+    /// Make a definition or reduce an expression
+    fn statement(&mut self, statement: Statement) {
+        match statement {
+            Statement::Definition(name, ast) => {
+                self.env = std::mem::take(&mut self.env).with(name, ast)
+            }
+            Statement::Expression(ast) => self.evaluate(self.env.resolve(ast)),
+        }
+    }
+
+    /// Limits of the reduction for the current step budget, the time limit starts now
+    fn limits(&self) -> ReductionLimits {
+        ReductionLimits {
+            fuel: self.fuel,
+            deadline: self.timeout.map(|timeout| Instant::now() + timeout),
+        }
+    }
+
+    /// Reduce a term within the step budget and print the result
+    fn evaluate(&self, ast: AST) {
+        let limits = self.limits();
+        // Only the rewriting of the tree has single steps to print
+        let result = match (self.trace, self.backend) {
+            (true, Backend::Tree) => self.trace(ast, &limits),
+            _ => self.backend.reduce(ast, &self.order, &limits),
+        };
+        // Eta reduction of a beta normal form cannot create new beta redexes
+        let result = match result {
+            ReductionResult::Normalized(ast) if self.eta => {
+                ReductionResult::Normalized(ast.eta_reduce())
+            }
+            _ => result,
+        };
+        match result {
+            ReductionResult::Normalized(ast) if self.decode => {
+                match ast.decode(self.encoding.encoding()) {
+                    Some(value) => println!("{}    = {}", self.printer.print(&ast), value),
+                    None => println!("{}", self.printer.print(&ast)),
+                }
+            }
+            result => println!("{}", result.show(&self.printer)),
+        }
+    }
+
+    /// Print the combinator terms of every bracket abstraction with their sizes, and whether
+    /// they turn back into a term beta-eta-equivalent to the original one. The translation is
+    /// printed first, the check may not finish for a term without a normal form
+    fn ski(&self, ast: AST) {
+        println!("{:<8} (size {}): {}", "lambda", ast.size(), ast);
+        for algorithm in [BracketAlgorithm::Naive, BracketAlgorithm::Turner] {
+            let ski = ast.to_ski(algorithm);
+            print!(
+                "{:<8} (size {}): {}    ",
+                algorithm.to_string(),
+                ski.size(),
+                ski
+            );
+            let _ = io::stdout().flush();
+            // The check gets a time limit of its own, even when the reductions have none
+            let limits = self.limits();
+            let deadline = Instant::now() + SKI_CHECK_TIMEOUT;
+            let limits = match limits.deadline {
+                Some(other) if other < deadline => limits,
+                _ => limits.with_deadline(deadline),
+            };
+            let round_trip = match ski.to_ast().beta_eta_equivalent(&ast, &limits) {
+                Some(true) => "equivalent",
+                Some(false) => "NOT equivalent",
+                None => "unknown, not normalized within the limits",
+            };
+            println!("({})", round_trip);
+        }
+    }
+
+    /// Reduce the term by call-by-need and print the counters of every argument
+    fn need(&self, ast: AST) {
+        let machine = CallByNeed::new().with_report();
+        let machine = match self.fuel {
+            Some(fuel) => machine.with_fuel(fuel),
+            None => machine,
+        };
+        let mut machine = match self.limits().deadline {
+            Some(deadline) => machine.with_deadline(deadline),
+            None => machine,
+        };
+        match machine.normalize(&ast) {
+            Ok(ast) => println!("{}", ast),
+            // The table of a diverging term is only as long as the fuel, so it is left out
+            Err(err) => return println!("{}", err),
+        }
+        println!("{:>8} {:>10}  argument", "forced", "evaluated");
+        for report in machine.report() {
+            println!(
+                "{:>8} {:>10}  {}",
+                report.forced, report.evaluated, report.argument
+            );
+        }
+    }
+
+    /// Compile the term, run it on the virtual machine and print the code and the counters
+    fn vm(&self, ast: AST) {
+        let program = Program::compile(&ast);
+        let mut vm = Vm::new(&program);
+        let result = vm.run(&self.limits());
+        print!("{}", program);
+        println!("{}", result);
+        let stats = vm.stats();
+        println!(
+            "instructions: {}, steps: {}, peak stack: {}, heap cells: {}",
+            stats.instructions, stats.steps, stats.peak_stack, stats.heap_cells
+        );
+    }
+
+    /// Print every step of the reduction as it happens
+    fn trace(&self, ast: AST, limits: &ReductionLimits) -> ReductionResult {
+        println!("   {}", self.printer.print(&ast));
+        let mut trace = ast.trace(Some(&self.order));
+        loop {
+            if limits.fuel.is_some_and(|fuel| trace.steps() >= fuel) {
+                return ReductionResult::OutOfFuel(trace.term().clone(), trace.steps());
+            }
+            if limits
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+            {
+                return ReductionResult::TimedOut(trace.term().clone());
+            }
+            match trace.next() {
+                Some(step) => println!(
+                    "-> {}    [{}: {}]",
+                    self.printer.print(&step.term),
+                    step.order,
+                    self.printer.print(&step.redex)
+                ),
+                None => return ReductionResult::Normalized(trace.term().clone()),
+            }
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use crate::ast::Term;
+use crate::ast::AST;
+use crate::ski::{Combinator, Ski};
+use std::fmt;
+use std::str::FromStr;
+
+/// Type represents the algorithms that remove a variable from a combinator term
+///  - *Naive*: only S, K and I, every application is split with S
+///  - *Turner*: also B, C and W, which pass the variable only to the side that uses it,
+///    and eta reduction, so the terms grow much less
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BracketAlgorithm {
+    Naive,
+    #[default]
+    Turner,
+}
+
+impl FromStr for BracketAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "naive" => Ok(BracketAlgorithm::Naive),
+            "turner" => Ok(BracketAlgorithm::Turner),
+            _ => Err(format!(
+                "Unknown bracket abstraction '{}', expected naive or turner",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for BracketAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BracketAlgorithm::Naive => write!(f, "naive"),
+            BracketAlgorithm::Turner => write!(f, "turner"),
+        }
+    }
+}
+
+impl BracketAlgorithm {
+    /// The bracket abstraction [x] M, a term without x that applied to any N behaves
+    /// as M with N for x
+    pub fn abstract_var(&self, name: &str, term: Ski) -> Ski {
+        match self {
+            BracketAlgorithm::Naive => naive(name, term),
+            BracketAlgorithm::Turner => turner(name, term),
+        }
+    }
+}
+
+/// [x] x = I, [x] y = K y, [x] (M N) = S ([x] M) ([x] N)
+fn naive(name: &str, term: Ski) -> Ski {
+    match term {
+        Ski::Var(ref s) if s == name => Ski::Comb(Combinator::I),
+        Ski::Var(_) | Ski::Comb(_) => Ski::apply(Ski::Comb(Combinator::K), term),
+        Ski::Apply(f, arg) => Ski::apply(
+            Ski::apply(Ski::Comb(Combinator::S), naive(name, *f)),
+            naive(name, *arg),
+        ),
+    }
+}
+
+/// The naive rules, with a term without x abstracted by K as a whole and an application
+/// split by the side that uses x
+fn turner(name: &str, term: Ski) -> Ski {
+    if !term.occurs(name) {
+        return Ski::apply(Ski::Comb(Combinator::K), term);
+    }
+    match term {
+        Ski::Var(_) | Ski::Comb(_) => Ski::Comb(Combinator::I),
+        Ski::Apply(f, arg) => match (f.occurs(name), arg.as_ref()) {
+            // Eta reduction, [x] (M x) = M
+            (false, Ski::Var(s)) if s == name => *f,
+            // [x] (M N) = B M ([x] N)
+            (false, _) => Ski::apply(Ski::apply(Ski::Comb(Combinator::B), *f), turner(name, *arg)),
+            (true, _) => match (arg.occurs(name), *f) {
+                // [x] (M x) = W ([x] M), so [x] (M x x) = W M and [x] (x x) = W I
+                (true, f) if is_var(&arg, name) => {
+                    Ski::apply(Ski::Comb(Combinator::W), turner(name, f))
+                }
+                // [x] (M N) = C ([x] M) N
+                (false, f) => {
+                    Ski::apply(Ski::apply(Ski::Comb(Combinator::C), turner(name, f)), *arg)
+                }
+                // [x] (M N) = S ([x] M) ([x] N)
+                (true, f) => Ski::apply(
+                    Ski::apply(Ski::Comb(Combinator::S), turner(name, f)),
+                    turner(name, *arg),
+                ),
+            },
+        },
+    }
+}
+
+fn is_var(term: &Ski, name: &str) -> bool {
+    matches!(term, Ski::Var(s) if s == name)
+}
+
+impl AST {
+    /// Translate the term into combinatory logic. Abstractions are removed from the innermost
+    /// one outwards, the free variables stay as they are
+    pub fn to_ski(&self, algorithm: BracketAlgorithm) -> Ski {
+        match &self.term {
+            Term::Var(s) => Ski::Var(s.clone()),
+            Term::Abstr(param, body) => match &param.term {
+                Term::Var(name) => algorithm.abstract_var(name, body.to_ski(algorithm)),
+                _ => panic!("Abstraction parameter must be a variable"),
+            },
+            Term::Apply(f, arg) => Ski::apply(f.to_ski(algorithm), arg.to_ski(algorithm)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BracketAlgorithm;
+    use crate::ast::AST;
+    use crate::beta::ReductionLimits;
+    use crate::eta::EtaReduction;
+
+    const TERMS: [&str; 7] = [
+        "$x -> x",
+        "$x, y -> x",
+        "$x -> x x",
+        "$f, g, x -> f (g x)",
+        "$x, y -> y x x",
+        "$f, x -> f (f x)",
+        "$p -> p ($a, b -> b) a",
+    ];
+
+    fn translate(input: &str, algorithm: BracketAlgorithm) -> String {
+        AST::eval(input).unwrap().to_ski(algorithm).to_string()
+    }
+
+    #[test]
+    fn round_trip() {
+        let limits = ReductionLimits {
+            fuel: Some(10_000),
+            deadline: None,
+        };
+        for algorithm in [BracketAlgorithm::Naive, BracketAlgorithm::Turner] {
+            for input in TERMS {
+                let ast = AST::eval(input).unwrap();
+                let back = ast.to_ski(algorithm).to_ast();
+                assert_eq!(
+                    back.beta_eta_equivalent(&ast, &limits),
+                    Some(true),
+                    "{} by {}",
+                    input,
+                    algorithm
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn naive() {
+        assert_eq!(translate("$x -> x", BracketAlgorithm::Naive), "I");
+        assert_eq!(
+            translate("$x, y -> x", BracketAlgorithm::Naive),
+            "S (K K) I"
+        );
+        assert_eq!(translate("$x -> x x", BracketAlgorithm::Naive), "S I I");
+    }
+
+    #[test]
+    fn turner() {
+        assert_eq!(translate("$x, y -> x", BracketAlgorithm::Turner), "K");
+        assert_eq!(
+            translate("$f, g, x -> f (g x)", BracketAlgorithm::Turner),
+            "B"
+        );
+        assert_eq!(
+            translate("$f, x, y -> f y x", BracketAlgorithm::Turner),
+            "C"
+        );
+        assert_eq!(translate("$x -> x x", BracketAlgorithm::Turner), "W I");
+        assert_eq!(translate("$f, x -> f x x", BracketAlgorithm::Turner), "W");
+        assert_eq!(translate("$x -> a", BracketAlgorithm::Turner), "K a");
+    }
+}
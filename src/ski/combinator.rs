@@ -0,0 +1,103 @@
+use crate::ast::AST;
+use crate::encodings::build::{apply, lambda, var};
+use std::fmt;
+
+/// Type represents the basic combinators, each one given by its reduction rule
+///  - *S*: S x y z => x z (y z)
+///  - *K*: K x y => x
+///  - *I*: I x => x
+///  - *B*: B x y z => x (y z)
+///  - *C*: C x y z => x z y
+///  - *W*: W x y => x y y
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combinator {
+    S,
+    K,
+    I,
+    B,
+    C,
+    W,
+}
+
+/// Type represents a term of combinatory logic, which has no abstractions
+///  - *Var*: a variable, e.g. x
+///  - *Comb*: a combinator, e.g. S
+///  - *Apply*: an application of a term to another term, e.g. K x
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ski {
+    Var(String),
+    Comb(Combinator),
+    Apply(Box<Ski>, Box<Ski>),
+}
+
+impl Combinator {
+    /// The lambda term that behaves the same as the combinator
+    pub fn to_ast(self) -> AST {
+        match self {
+            Combinator::S => lambda(
+                &["x", "y", "z"],
+                apply(var("x"), [var("z"), apply(var("y"), [var("z")])]),
+            ),
+            Combinator::K => lambda(&["x", "y"], var("x")),
+            Combinator::I => lambda(&["x"], var("x")),
+            Combinator::B => lambda(
+                &["x", "y", "z"],
+                apply(var("x"), [apply(var("y"), [var("z")])]),
+            ),
+            Combinator::C => lambda(&["x", "y", "z"], apply(var("x"), [var("z"), var("y")])),
+            Combinator::W => lambda(&["x", "y"], apply(var("x"), [var("y"), var("y")])),
+        }
+    }
+}
+
+impl Ski {
+    pub fn apply(f: Ski, arg: Ski) -> Ski {
+        Ski::Apply(Box::new(f), Box::new(arg))
+    }
+
+    /// Whether the variable occurs in the term
+    pub fn occurs(&self, name: &str) -> bool {
+        match self {
+            Ski::Var(s) => s == name,
+            Ski::Comb(_) => false,
+            Ski::Apply(f, arg) => f.occurs(name) || arg.occurs(name),
+        }
+    }
+
+    /// Number of nodes of the term, i.e. variables, combinators and applications
+    pub fn size(&self) -> usize {
+        match self {
+            Ski::Var(_) | Ski::Comb(_) => 1,
+            Ski::Apply(f, arg) => 1 + f.size() + arg.size(),
+        }
+    }
+
+    /// Embed the term back into the lambda calculus, every combinator becomes its lambda term
+    pub fn to_ast(&self) -> AST {
+        match self {
+            Ski::Var(s) => AST::var(s.clone()),
+            Ski::Comb(combinator) => combinator.to_ast(),
+            Ski::Apply(f, arg) => AST::apply(f.to_ast(), arg.to_ast()),
+        }
+    }
+}
+
+impl fmt::Display for Combinator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl fmt::Display for Ski {
+    /// Print the term with application associating to the left, i.e. S (K x) I
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ski::Var(s) => write!(f, "{}", s),
+            Ski::Comb(combinator) => write!(f, "{}", combinator),
+            Ski::Apply(function, arg) => match arg.as_ref() {
+                Ski::Apply(_, _) => write!(f, "{} ({})", function, arg),
+                _ => write!(f, "{} {}", function, arg),
+            },
+        }
+    }
+}
@@ -0,0 +1,6 @@
+//! This module handles combinatory logic and the translation of lambda terms into it
+pub mod bracket;
+pub mod combinator;
+
+pub use self::bracket::BracketAlgorithm;
+pub use self::combinator::{Combinator, Ski};
@@ -0,0 +1,6 @@
+//! This module handles positions in the source text and the rendering of diagnostics
+pub mod snippet;
+pub mod span;
+
+pub use self::snippet::render_snippet;
+pub use self::span::Span;
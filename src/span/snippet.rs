@@ -0,0 +1,36 @@
+use crate::span::Span;
+
+/// Render a message as an annotated snippet of the source, i.e.
+///
+/// error: Unexpected token in expression body: '->'
+///  --> 1:7
+///   |
+/// 1 | $x -> -> x
+///   |       ^^
+pub fn render_snippet(source: &str, span: Span, message: &str) -> String {
+    let line_number = span.line.max(1);
+    let line = source.lines().nth(line_number - 1).unwrap_or("");
+    let gutter = " ".repeat(line_number.to_string().len());
+    // Only the part of the span on its first line is underlined, with at least one caret
+    let start = span.start.min(source.len());
+    let end = span.end.clamp(start, source.len());
+    let width = source[start..end]
+        .lines()
+        .next()
+        .map_or(0, |text| text.chars().count())
+        .max(1);
+    let offset = " ".repeat(span.column.max(1) - 1);
+    format!(
+        "error: {}\n{}--> {}:{}\n{} |\n{} | {}\n{} | {}{}",
+        message,
+        gutter,
+        line_number,
+        span.column.max(1),
+        gutter,
+        line_number,
+        line,
+        gutter,
+        offset,
+        "^".repeat(width)
+    )
+}
@@ -0,0 +1,29 @@
+/// Type represents a region of the source text
+///  - *start*, *end*: byte offsets of the region
+///  - *line*, *column*: 1-based position of the start of the region, counted in characters
+#[derive(Debug, Default, Clone, Copy, Hash, Eq, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
+        Span {
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+
+    /// Return a span covering both spans, assuming self comes first
+    pub fn join(self, other: Span) -> Span {
+        Span {
+            end: other.end.max(self.end),
+            ..self
+        }
+    }
+}
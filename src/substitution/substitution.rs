@@ -40,18 +40,24 @@ impl AST {
 impl Substitution for AST {
     fn substitute(self, var: Term, term: AST) -> Result<AST, SubstitutionError> {
         self.check_substitution(&var, &term)?;
+        // Nothing to substitute, including a variable shadowed by an abstraction
+        if !self.free_vars.contains(&var) {
+            return Ok(self);
+        }
 
-        match self.term.clone() {
-            // Variable - substitute if it is equal to var
-            Term::Var(f) => match var {
-                Term::Var(ref s) if s == &f => Ok(term),
-                _ => Ok(self),
-            },
+        // The subterms are moved rather than copied, only the rebuilt levels cost anything
+        match self.term {
+            // Variable - it is free and so equal to var, substitute it
+            Term::Var(_) => Ok(term),
             // Application - recursively substitute left and right sides
             Term::Apply(f, arg) => {
                 let lhs = (*f).substitute(var.clone(), term.clone())?;
                 let rhs = (*arg).substitute(var, term)?;
-                Ok(AST::apply(lhs, rhs))
+                // A let binding stays one
+                Ok(AST {
+                    is_let: self.is_let,
+                    ..AST::apply(lhs, rhs)
+                })
             }
             // Abstraction - first check if the current parameter is free somewhere in
             // the term we are replacing with
@@ -66,7 +72,12 @@ impl Substitution for AST {
                         (*body).substitute((*param).term.clone(), fresh_var.clone())?;
                     // After renaming, substitute in the body
                     let new_body = renamed_body.substitute(var, term)?;
-                    Ok(AST::abstr(fresh_var, new_body))
+                    // The renamed parameter keeps its type annotation
+                    let fresh_param = AST {
+                        annotation: param.annotation.clone(),
+                        ..fresh_var
+                    };
+                    Ok(AST::abstr(fresh_param, new_body))
                 }
                 // Otherwise just substitute in the body
                 false => {
@@ -77,3 +88,53 @@ impl Substitution for AST {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Substitution;
+    use crate::ast::Term;
+    use crate::ast::AST;
+    use crate::beta::BetaReduction;
+
+    fn parse(input: &str) -> AST {
+        AST::eval(input).unwrap()
+    }
+
+    fn substitute(input: &str, var: &str, term: &str) -> AST {
+        parse(input)
+            .substitute(Term::Var(var.to_string()), parse(term))
+            .unwrap()
+    }
+
+    #[test]
+    fn substitute_free_occurrences() {
+        assert_eq!(substitute("x", "x", "a"), parse("a"));
+        assert_eq!(
+            substitute("x ($y -> x y)", "x", "a"),
+            parse("a ($y -> a y)")
+        );
+    }
+
+    #[test]
+    fn shadowed_variable_is_not_substituted() {
+        assert_eq!(substitute("$x -> x", "x", "a"), parse("$x -> x"));
+        assert_eq!(substitute("x ($x -> x)", "x", "a"), parse("a ($x -> x)"));
+        assert_eq!(
+            substitute("$y -> $x -> x y", "x", "a"),
+            parse("$y -> $x -> x y")
+        );
+    }
+
+    #[test]
+    fn reduce_under_shadowing_binder() {
+        assert_eq!(parse("($x -> $x -> x) a").reduce(None), parse("$x -> x"));
+    }
+
+    #[test]
+    fn capture_is_avoided() {
+        let ast = parse("$y -> x y")
+            .substitute(Term::Var("x".to_string()), parse("y"))
+            .unwrap_or_else(|_| panic!("Renaming the parameter avoids the capture"));
+        assert_eq!(ast, parse("$z -> y z"));
+    }
+}
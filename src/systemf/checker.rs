@@ -0,0 +1,297 @@
+use crate::span::render_snippet;
+use crate::systemf::{FTerm, FTermKind};
+use crate::types::Type;
+use std::fmt;
+
+/// Type for errors of System F type checking, each one carries the offending subterm
+///  - *UnboundVariable*: a free variable, which has no type
+///  - *NotAFunction*: a term applied to an argument, whose type is not a function type
+///  - *NotPolymorphic*: a term applied to a type, whose type is not a polymorphic type
+///  - *Mismatch*: an argument whose type differs from the parameter type of the function
+#[derive(Debug, Clone)]
+pub enum FTypeError {
+    UnboundVariable(Box<FTerm>),
+    NotAFunction(Box<FTerm>, Type),
+    NotPolymorphic(Box<FTerm>, Type),
+    Mismatch {
+        term: Box<FTerm>,
+        expected: Type,
+        found: Type,
+    },
+}
+
+impl fmt::Display for FTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FTypeError::UnboundVariable(var) => {
+                write!(f, "The variable '{}' is not bound, so it has no type", var)
+            }
+            FTypeError::NotAFunction(term, ty) => {
+                write!(f, "'{}' has type {}, so it cannot be applied", term, ty)
+            }
+            FTypeError::NotPolymorphic(term, ty) => write!(
+                f,
+                "'{}' has type {}, so it cannot be applied to a type",
+                term, ty
+            ),
+            FTypeError::Mismatch {
+                term,
+                expected,
+                found,
+            } => write!(
+                f,
+                "'{}' has type {}, but {} is expected",
+                term, found, expected
+            ),
+        }
+    }
+}
+
+impl FTypeError {
+    /// The subterm the error is about
+    pub fn term(&self) -> &FTerm {
+        match self {
+            FTypeError::UnboundVariable(term)
+            | FTypeError::NotAFunction(term, _)
+            | FTypeError::NotPolymorphic(term, _)
+            | FTypeError::Mismatch { term, .. } => term,
+        }
+    }
+
+    /// Render the error as an annotated snippet of the input if the subterm was parsed from it
+    pub fn render(&self, source: &str) -> String {
+        match self.term().span {
+            Some(span) => render_snippet(source, span, &self.to_string()),
+            None => format!("error: {}", self),
+        }
+    }
+}
+
+/// A name that is not in the list, made by appending a number, i.e. a => a1
+fn fresh_name(name: &str, used: &[String]) -> String {
+    (1..)
+        .map(|n| format!("{}{}", name, n))
+        .find(|fresh| !used.contains(fresh))
+        .expect("There is always an unused name")
+}
+
+impl Type {
+    /// Collect the names of the type that are not bound by a quantifier
+    fn free_names(&self, names: &mut Vec<String>) {
+        match self {
+            Type::Base(name) if !names.contains(name) => names.push(name.clone()),
+            Type::Base(_) | Type::Var(_) => (),
+            Type::Arrow(from, to) => {
+                from.free_names(names);
+                to.free_names(names);
+            }
+            Type::Forall(name, body) => {
+                let mut inner = Vec::new();
+                body.free_names(&mut inner);
+                for free in inner.into_iter().filter(|free| free != name) {
+                    if !names.contains(&free) {
+                        names.push(free);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Replace the free occurrences of the name with the type. A quantifier whose variable
+    /// is free in the type is renamed first, so the type is not captured
+    pub fn substitute_type(&self, name: &str, ty: &Type) -> Type {
+        match self {
+            Type::Base(s) if s == name => ty.clone(),
+            Type::Base(_) | Type::Var(_) => self.clone(),
+            Type::Arrow(from, to) => {
+                Type::arrow(from.substitute_type(name, ty), to.substitute_type(name, ty))
+            }
+            // The name is shadowed by the quantifier
+            Type::Forall(var, _) if var == name => self.clone(),
+            Type::Forall(var, body) => {
+                let mut free = Vec::new();
+                ty.free_names(&mut free);
+                match free.contains(var) {
+                    true => {
+                        body.free_names(&mut free);
+                        free.push(name.to_string());
+                        let fresh = fresh_name(var, &free);
+                        let body = body.substitute_type(var, &Type::Base(fresh.clone()));
+                        Type::forall(fresh, body.substitute_type(name, ty))
+                    }
+                    false => Type::forall(var.clone(), body.substitute_type(name, ty)),
+                }
+            }
+        }
+    }
+}
+
+impl FTerm {
+    /// Collect every type name used in the term, bound or free
+    fn type_names(&self, names: &mut Vec<String>) {
+        match &self.term {
+            FTermKind::Var(_) => (),
+            FTermKind::Abstr(_, ty, body) => {
+                ty.free_names(names);
+                body.type_names(names);
+            }
+            FTermKind::Apply(f, arg) => {
+                f.type_names(names);
+                arg.type_names(names);
+            }
+            FTermKind::TypeAbstr(name, body) => {
+                names.push(name.clone());
+                body.type_names(names);
+            }
+            FTermKind::TypeApply(term, ty) => {
+                ty.free_names(names);
+                term.type_names(names);
+            }
+        }
+    }
+
+    /// Rename the free occurrences of a type variable in the types of the term.
+    /// The terms keep their spans, so errors in the renamed term still point at the source
+    fn rename_type(&self, name: &str, fresh: &str) -> FTerm {
+        let fresh_type = Type::Base(fresh.to_string());
+        let renamed = match &self.term {
+            FTermKind::Var(_) => return self.clone(),
+            FTermKind::Abstr(param, ty, body) => FTerm::abstr(
+                param.clone(),
+                ty.substitute_type(name, &fresh_type),
+                body.rename_type(name, fresh),
+            ),
+            FTermKind::Apply(f, arg) => {
+                FTerm::apply(f.rename_type(name, fresh), arg.rename_type(name, fresh))
+            }
+            // The name is shadowed by the type abstraction
+            FTermKind::TypeAbstr(var, _) if var == name => return self.clone(),
+            FTermKind::TypeAbstr(var, body) => {
+                FTerm::type_abstr(var.clone(), body.rename_type(name, fresh))
+            }
+            FTermKind::TypeApply(term, ty) => FTerm::type_apply(
+                term.rename_type(name, fresh),
+                ty.substitute_type(name, &fresh_type),
+            ),
+        };
+        FTerm {
+            span: self.span,
+            ..renamed
+        }
+    }
+
+    /// Type of the term in the context of the types of the enclosing parameters, the innermost last
+    fn type_in(&self, context: &mut Vec<(String, Type)>) -> Result<Type, FTypeError> {
+        match &self.term {
+            FTermKind::Var(s) => context
+                .iter()
+                .rev()
+                .find(|(name, _)| name == s)
+                .map(|(_, ty)| ty.clone())
+                .ok_or_else(|| FTypeError::UnboundVariable(Box::new(self.clone()))),
+            FTermKind::Abstr(param, ty, body) => {
+                context.push((param.clone(), ty.clone()));
+                let body = body.type_in(context);
+                context.pop();
+                Ok(Type::arrow(ty.clone(), body?))
+            }
+            FTermKind::Apply(f, arg) => match f.type_in(context)? {
+                Type::Arrow(from, to) => {
+                    let found = arg.type_in(context)?;
+                    match from.alpha_equivalent(&found) {
+                        true => Ok(*to),
+                        false => Err(FTypeError::Mismatch {
+                            term: arg.clone(),
+                            expected: *from,
+                            found,
+                        }),
+                    }
+                }
+                ty => Err(FTypeError::NotAFunction(f.clone(), ty)),
+            },
+            FTermKind::TypeAbstr(name, body) => {
+                let mut free = Vec::new();
+                for (_, ty) in context.iter() {
+                    ty.free_names(&mut free);
+                }
+                // The variable must not be free in the context, otherwise a parameter of the
+                // outer type would be quantified, so the abstraction gets a new name
+                match free.contains(name) {
+                    true => {
+                        body.type_names(&mut free);
+                        let fresh = fresh_name(name, &free);
+                        let body = body.rename_type(name, &fresh).type_in(context)?;
+                        Ok(Type::forall(fresh, body))
+                    }
+                    false => Ok(Type::forall(name.clone(), body.type_in(context)?)),
+                }
+            }
+            // The type argument replaces the quantified variable
+            FTermKind::TypeApply(term, ty) => match term.type_in(context)? {
+                Type::Forall(name, body) => Ok(body.substitute_type(&name, ty)),
+                found => Err(FTypeError::NotPolymorphic(term.clone(), found)),
+            },
+        }
+    }
+
+    /// Type of a closed term
+    pub fn type_of(&self) -> Result<Type, FTypeError> {
+        self.type_in(&mut Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FTypeError;
+    use crate::systemf::FTerm;
+
+    fn type_of(input: &str) -> Result<String, FTypeError> {
+        FTerm::eval(input)
+            .unwrap()
+            .type_of()
+            .map(|ty| ty.to_string())
+    }
+
+    /// The source the subterm of the error was parsed from
+    fn error_source(input: &str) -> String {
+        let span = type_of(input).unwrap_err().term().span.unwrap();
+        input[span.start..span.end].to_string()
+    }
+
+    #[test]
+    fn polymorphic_identity() {
+        assert_eq!(type_of("/\\a. $x: a -> x").unwrap(), "\\/a. a -> a");
+        assert_eq!(type_of("(/\\a. $x: a -> x) [A]").unwrap(), "A -> A");
+    }
+
+    #[test]
+    fn quantified_names_do_not_matter() {
+        assert_eq!(
+            type_of("($g: \\/a. a -> a -> g) (/\\b. $x: b -> x)").unwrap(),
+            "\\/a. a -> a"
+        );
+    }
+
+    #[test]
+    fn errors_point_at_the_subterm() {
+        assert_eq!(error_source("(/\\a. $x: a -> x) [A] y"), "y");
+        assert_eq!(error_source("/\\a. $f: a -> a, x: b -> f x"), "x");
+        assert_eq!(error_source("$x: A -> x [B]"), "x");
+        assert_eq!(error_source("$f: A -> A, x: A -> f x (f x)"), "f x");
+        assert!(matches!(
+            type_of("$x: A -> x x"),
+            Err(FTypeError::NotAFunction(_, _))
+        ));
+    }
+
+    #[test]
+    fn renamed_terms_keep_spans() {
+        // The type abstraction over a is renamed, because a is free in the type of y
+        let input = "$y: a -> (/\\a. $x: a -> z) [A]";
+        assert!(matches!(
+            type_of(input),
+            Err(FTypeError::UnboundVariable(_))
+        ));
+        assert_eq!(error_source(input), "z");
+    }
+}
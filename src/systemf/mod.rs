@@ -0,0 +1,7 @@
+//! This module handles System F, the lambda calculus with explicit type abstraction and application
+pub mod checker;
+pub mod parser;
+pub mod term;
+
+pub use self::parser::parse_fterm;
+pub use self::term::{FTerm, FTermKind};
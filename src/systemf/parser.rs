@@ -0,0 +1,265 @@
+use crate::lexer::{Token, TokenKind};
+use crate::parser::annotation::{annotation, type_expression};
+use crate::parser::parser::trailing_token;
+use crate::parser::ParsingError;
+use crate::span::Span;
+use crate::systemf::FTerm;
+
+/// Error for a term that is missing, pointing at the token found instead
+fn missing_term(tokens: &[Token], context: &str) -> ParsingError {
+    match tokens {
+        [token, ..] => ParsingError::InvalidSyntax(
+            format!("Unexpected {} {}", token.kind, context),
+            token.span,
+        ),
+        [] => ParsingError::InvalidSyntax(
+            format!("Unexpected end of input {}", context),
+            Span::default(),
+        ),
+    }
+}
+
+/// Span of a term built by the parser
+fn span_of(term: &FTerm) -> Span {
+    term.span.unwrap_or_default()
+}
+
+/// Parser of a whole term up to the token that ends it, which is left in the rest
+fn term<'a>(tokens: &'a [Token], context: &str) -> Result<(FTerm, &'a [Token]), ParsingError> {
+    match tokens {
+        [Token {
+            kind: TokenKind::Lambda,
+            span,
+        }, rest @ ..] => lambda(*span, rest),
+        [Token {
+            kind: TokenKind::TypeLambda,
+            span,
+        }, rest @ ..] => type_lambda(*span, rest),
+        _ => application(tokens, context),
+    }
+}
+
+/// Parser of a variable or a term in parentheses
+fn atom<'a>(tokens: &'a [Token], context: &str) -> Result<(FTerm, &'a [Token]), ParsingError> {
+    match tokens {
+        [Token {
+            kind: TokenKind::Var(s),
+            span,
+        }, rest @ ..] => Ok((FTerm::var(s.clone()).with_span(*span), rest)),
+        [Token {
+            kind: TokenKind::LParen,
+            span,
+        }, rest @ ..] => {
+            let (term, rest) = term(rest, "after '('")?;
+            match rest {
+                [Token {
+                    kind: TokenKind::RParen,
+                    span: rparen,
+                }, rest @ ..] => Ok((term.with_span(span.join(*rparen)), rest)),
+                [token, ..] if token.kind != TokenKind::Eof => Err(ParsingError::MismatchedParens(
+                    format!("Expected ')' but found {}", token.kind),
+                    token.span,
+                )),
+                _ => Err(ParsingError::MismatchedParens(
+                    "Unclosed '(' at the end of input".to_string(),
+                    *span,
+                )),
+            }
+        }
+        _ => Err(missing_term(tokens, context)),
+    }
+}
+
+/// Parser of a chain of applications to terms and types, i.e. f [A] x y => ((f [A]) x) y.
+/// An abstraction as the last argument extends as far to the right as possible
+fn application<'a>(
+    tokens: &'a [Token],
+    context: &str,
+) -> Result<(FTerm, &'a [Token]), ParsingError> {
+    let (mut function, mut tokens) = atom(tokens, context)?;
+    loop {
+        match tokens {
+            [Token {
+                kind: TokenKind::LBracket,
+                span,
+            }, rest @ ..] => {
+                let (ty, rest) = type_expression(rest)?;
+                match rest {
+                    [Token {
+                        kind: TokenKind::RBracket,
+                        span: rbracket,
+                    }, rest @ ..] => {
+                        let span = span_of(&function).join(*rbracket);
+                        function = FTerm::type_apply(function, ty).with_span(span);
+                        tokens = rest;
+                    }
+                    [token, ..] => {
+                        return Err(ParsingError::InvalidSyntax(
+                            format!("Expected ']' after type argument, found {}", token.kind),
+                            token.span,
+                        ))
+                    }
+                    [] => {
+                        return Err(ParsingError::InvalidSyntax(
+                            "Unclosed '[' at the end of input".to_string(),
+                            *span,
+                        ))
+                    }
+                }
+            }
+            [Token {
+                kind: TokenKind::Lambda | TokenKind::TypeLambda,
+                ..
+            }, ..] => {
+                let (arg, rest) = term(tokens, "in argument")?;
+                let span = span_of(&function).join(span_of(&arg));
+                return Ok((FTerm::apply(function, arg).with_span(span), rest));
+            }
+            [Token {
+                kind: TokenKind::Var(_) | TokenKind::LParen,
+                ..
+            }, ..] => {
+                let (arg, rest) = atom(tokens, "in argument")?;
+                let span = span_of(&function).join(span_of(&arg));
+                function = FTerm::apply(function, arg).with_span(span);
+                tokens = rest;
+            }
+            _ => return Ok((function, tokens)),
+        }
+    }
+}
+
+/// Parser of an abstraction, whose parameters all need a type, i.e.
+/// $f: a -> a, x: a -> f x => $f: a -> a -> $x: a -> f x.
+/// The start is the span of the '$', where the outermost abstraction begins
+fn lambda(start: Span, tokens: &[Token]) -> Result<(FTerm, &[Token]), ParsingError> {
+    let mut params = Vec::new();
+    let mut tokens = tokens;
+    let body = loop {
+        match tokens {
+            [Token {
+                kind: TokenKind::Var(s),
+                span,
+            }, Token {
+                kind: TokenKind::Colon,
+                ..
+            }, rest @ ..] => {
+                let (ty, rest) = annotation(rest)?;
+                params.push((s.clone(), ty, *span));
+                match rest {
+                    [Token {
+                        kind: TokenKind::Comma,
+                        ..
+                    }, rest @ ..] => tokens = rest,
+                    [Token {
+                        kind: TokenKind::Arrow,
+                        ..
+                    }, rest @ ..] => break term(rest, "at the start of a lambda expression body")?,
+                    [token, ..] => {
+                        return Err(ParsingError::InvalidLambda(
+                            format!(
+                                "Expected ',' or '->' after the type of lambda param, found {}",
+                                token.kind
+                            ),
+                            token.span,
+                        ))
+                    }
+                    [] => return Err(missing_term(rest, "when reading lambda params")),
+                }
+            }
+            [Token {
+                kind: TokenKind::Var(s),
+                span,
+            }, ..] => {
+                return Err(ParsingError::InvalidLambda(
+                    format!(
+                        "The parameter '{}' needs a type, e.g. ${}: a -> {}",
+                        s, s, s
+                    ),
+                    *span,
+                ))
+            }
+            _ => return Err(missing_term(tokens, "in lambda params")),
+        }
+    };
+    let (term, rest) = body;
+    // The inner abstractions begin at their parameter
+    if let Some((_, _, first)) = params.first_mut() {
+        *first = start;
+    }
+    let term = params
+        .into_iter()
+        .rev()
+        .fold(term, |body, (param, ty, start)| {
+            let span = start.join(span_of(&body));
+            FTerm::abstr(param, ty, body).with_span(span)
+        });
+    Ok((term, rest))
+}
+
+/// Parser of a type abstraction, i.e. /\a, b. t => /\a. /\b. t.
+/// The start is the span of the '/\', where the outermost abstraction begins
+fn type_lambda(start: Span, tokens: &[Token]) -> Result<(FTerm, &[Token]), ParsingError> {
+    let mut names = Vec::new();
+    let mut tokens = tokens;
+    let body = loop {
+        match tokens {
+            [Token {
+                kind: TokenKind::Var(name),
+                span,
+            }, Token {
+                kind: TokenKind::Comma,
+                ..
+            }, rest @ ..] => {
+                names.push((name.clone(), *span));
+                tokens = rest;
+            }
+            [Token {
+                kind: TokenKind::Var(name),
+                span,
+            }, Token {
+                kind: TokenKind::Dot,
+                ..
+            }, rest @ ..] => {
+                names.push((name.clone(), *span));
+                break term(rest, "at the start of a type abstraction body")?;
+            }
+            [Token {
+                kind: TokenKind::Var(_),
+                ..
+            }, token, ..] => {
+                return Err(ParsingError::InvalidLambda(
+                    format!(
+                        "Expected ',' or '.' after type parameter, found {}",
+                        token.kind
+                    ),
+                    token.span,
+                ))
+            }
+            _ => return Err(missing_term(tokens, "in type lambda params")),
+        }
+    };
+    let (term, rest) = body;
+    // The inner abstractions begin at their type parameter
+    if let Some((_, first)) = names.first_mut() {
+        *first = start;
+    }
+    let term = names.into_iter().rev().fold(term, |body, (name, start)| {
+        let span = start.join(span_of(&body));
+        FTerm::type_abstr(name, body).with_span(span)
+    });
+    Ok((term, rest))
+}
+
+/// Parse a single System F term and check that the input is finished
+pub fn parse_fterm(tokens: &[Token]) -> Result<FTerm, ParsingError> {
+    let (term, rest) = term(tokens, "at the start of expression")?;
+    match rest {
+        []
+        | [Token {
+            kind: TokenKind::Eof,
+            ..
+        }, ..] => Ok(term),
+        [token, ..] => Err(trailing_token(token)),
+    }
+}
@@ -0,0 +1,148 @@
+use crate::ast::ast::EvalError;
+use crate::ast::AST;
+use crate::lexer::tokenize;
+use crate::span::Span;
+use crate::systemf::parse_fterm;
+use crate::types::Type;
+use std::fmt;
+
+/// Type represents the shape of a System F term. Every parameter has a type, so a term has
+/// at most one type
+///  - *Var*: a variable, e.g. x
+///  - *Abstr*: an abstraction with the type of its parameter, e.g. $x: a -> x
+///  - *Apply*: an application of a term to another term, e.g. f x
+///  - *TypeAbstr*: an abstraction over a type, e.g. /\a. $x: a -> x
+///  - *TypeApply*: an application of a term to a type, e.g. id [A]
+#[derive(Debug, Clone, PartialEq)]
+pub enum FTermKind {
+    Var(String),
+    Abstr(String, Type, Box<FTerm>),
+    Apply(Box<FTerm>, Box<FTerm>),
+    TypeAbstr(String, Box<FTerm>),
+    TypeApply(Box<FTerm>, Type),
+}
+
+/// Type represents a term of System F
+#[derive(Debug, Clone)]
+pub struct FTerm {
+    pub term: FTermKind,
+    /// Position in the source the term was parsed from, if any
+    pub span: Option<Span>,
+}
+
+/// Terms are equal by their shape, wherever they were parsed from
+impl PartialEq for FTerm {
+    fn eq(&self, other: &Self) -> bool {
+        self.term == other.term
+    }
+}
+
+impl FTerm {
+    fn new(term: FTermKind) -> FTerm {
+        FTerm { term, span: None }
+    }
+
+    pub fn var(s: String) -> FTerm {
+        FTerm::new(FTermKind::Var(s))
+    }
+
+    pub fn abstr(param: String, ty: Type, body: FTerm) -> FTerm {
+        FTerm::new(FTermKind::Abstr(param, ty, Box::new(body)))
+    }
+
+    pub fn apply(f: FTerm, arg: FTerm) -> FTerm {
+        FTerm::new(FTermKind::Apply(Box::new(f), Box::new(arg)))
+    }
+
+    pub fn type_abstr(name: String, body: FTerm) -> FTerm {
+        FTerm::new(FTermKind::TypeAbstr(name, Box::new(body)))
+    }
+
+    pub fn type_apply(term: FTerm, ty: Type) -> FTerm {
+        FTerm::new(FTermKind::TypeApply(Box::new(term), ty))
+    }
+
+    /// Return the term with its position in the source
+    pub fn with_span(self, span: Span) -> FTerm {
+        FTerm {
+            span: Some(span),
+            ..self
+        }
+    }
+
+    /// Erase the types, which leaves the untyped term with the same reductions
+    pub fn erase(&self) -> AST {
+        match &self.term {
+            FTermKind::Var(s) => AST::var(s.clone()),
+            FTermKind::Abstr(param, _, body) => AST::abstr(AST::var(param.clone()), body.erase()),
+            FTermKind::Apply(f, arg) => AST::apply(f.erase(), arg.erase()),
+            // Type abstraction and application have no computational content
+            FTermKind::TypeAbstr(_, body) => body.erase(),
+            FTermKind::TypeApply(term, _) => term.erase(),
+        }
+    }
+
+    pub fn eval(input: &str) -> Result<FTerm, EvalError> {
+        let tokens =
+            tokenize(input).map_err(|err| EvalError::TokenizationError(err.render(input)))?;
+        parse_fterm(&tokens).map_err(|err| EvalError::ParsingError(err.render(input)))
+    }
+
+    /// Recursively write a term. An abstraction extends as far to the right as possible,
+    /// so it only goes without parentheses if it is the rightmost part of the expression
+    fn write(&self, rightmost: bool, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.term {
+            FTermKind::Var(s) => write!(f, "{}", s),
+            FTermKind::Abstr(_, _, _) | FTermKind::TypeAbstr(_, _) if !rightmost => {
+                write!(f, "(")?;
+                self.write(true, f)?;
+                write!(f, ")")
+            }
+            // Nested abstractions are printed as one with multiple parameters, the inverse
+            // of the parser's desugaring
+            FTermKind::Abstr(param, ty, body) => {
+                write!(f, "${}: {}", param, ty)?;
+                let mut body = body;
+                while let FTermKind::Abstr(param, ty, inner) = &body.term {
+                    write!(f, ", {}: {}", param, ty)?;
+                    body = inner;
+                }
+                write!(f, " -> ")?;
+                body.write(true, f)
+            }
+            FTermKind::TypeAbstr(name, body) => {
+                write!(f, "/\\{}", name)?;
+                let mut body = body;
+                while let FTermKind::TypeAbstr(name, inner) = &body.term {
+                    write!(f, ", {}", name)?;
+                    body = inner;
+                }
+                write!(f, ". ")?;
+                body.write(true, f)
+            }
+            FTermKind::Apply(function, arg) => {
+                function.write(false, f)?;
+                write!(f, " ")?;
+                match &arg.term {
+                    // An application on the right side has to be grouped
+                    FTermKind::Apply(_, _) | FTermKind::TypeApply(_, _) => {
+                        write!(f, "(")?;
+                        arg.write(true, f)?;
+                        write!(f, ")")
+                    }
+                    _ => arg.write(rightmost, f),
+                }
+            }
+            FTermKind::TypeApply(term, ty) => {
+                term.write(false, f)?;
+                write!(f, " [{}]", ty)
+            }
+        }
+    }
+}
+
+impl fmt::Display for FTerm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(true, f)
+    }
+}
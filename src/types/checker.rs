@@ -0,0 +1,210 @@
+use crate::ast::Term;
+use crate::ast::AST;
+use crate::span::render_snippet;
+use crate::types::Type;
+
+/// Type for errors of type checking, each one carries the offending subterm
+///  - *Unannotated*: a parameter without a type, whose type cannot be taken from an argument
+///  - *UnboundVariable*: a free variable, which has no type
+///  - *NotAFunction*: a term applied to an argument, whose type is not a function type
+///  - *Mismatch*: an argument whose type differs from the parameter type of the function
+///  - *Infinite*: a term whose type would have to contain itself, e.g. x x
+#[derive(Debug, Clone)]
+pub enum TypeError {
+    Unannotated(Box<AST>),
+    UnboundVariable(Box<AST>),
+    NotAFunction(Box<AST>, Type),
+    Mismatch {
+        term: Box<AST>,
+        expected: Type,
+        found: Type,
+    },
+    Infinite {
+        term: Box<AST>,
+        var: Type,
+        ty: Type,
+    },
+}
+
+impl TypeError {
+    /// The subterm the error is about
+    pub fn term(&self) -> &AST {
+        match self {
+            TypeError::Unannotated(term)
+            | TypeError::UnboundVariable(term)
+            | TypeError::NotAFunction(term, _)
+            | TypeError::Mismatch { term, .. }
+            | TypeError::Infinite { term, .. } => term,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            TypeError::Unannotated(param) => {
+                format!("The parameter '{}' needs a type annotation", param)
+            }
+            TypeError::UnboundVariable(var) => {
+                format!("The variable '{}' is not bound, so it has no type", var)
+            }
+            TypeError::NotAFunction(term, ty) => {
+                format!("'{}' has type {}, so it cannot be applied", term, ty)
+            }
+            TypeError::Mismatch {
+                term,
+                expected,
+                found,
+            } => format!(
+                "'{}' has type {}, but {} is expected",
+                term, found, expected
+            ),
+            TypeError::Infinite { term, var, ty } => format!(
+                "Cannot construct the infinite type {} = {} for '{}'",
+                var, ty, term
+            ),
+        }
+    }
+
+    /// Rename the type variables of the error to a, b, c, ... in the order they appear,
+    /// skipping the letters of the base types of the error
+    pub fn pretty(self) -> TypeError {
+        let (mut names, mut bases) = (Vec::new(), Vec::new());
+        match self {
+            TypeError::Unannotated(_) | TypeError::UnboundVariable(_) => self,
+            TypeError::NotAFunction(term, ty) => TypeError::NotAFunction(term, ty.pretty()),
+            TypeError::Mismatch {
+                term,
+                expected,
+                found,
+            } => {
+                expected.bases(&mut bases);
+                found.bases(&mut bases);
+                TypeError::Mismatch {
+                    term,
+                    expected: expected.rename(&mut names, &bases),
+                    found: found.rename(&mut names, &bases),
+                }
+            }
+            TypeError::Infinite { term, var, ty } => {
+                ty.bases(&mut bases);
+                TypeError::Infinite {
+                    term,
+                    var: var.rename(&mut names, &bases),
+                    ty: ty.rename(&mut names, &bases),
+                }
+            }
+        }
+    }
+
+    /// Render the error as an annotated snippet of the input if the subterm was parsed from it
+    pub fn render(&self, source: &str) -> String {
+        match self.term().span {
+            Some(span) => render_snippet(source, span, &self.message()),
+            None => format!("error: {}", self.message()),
+        }
+    }
+}
+
+pub trait TypeCheck {
+    fn type_of(&self) -> Result<Type, TypeError>;
+}
+
+impl AST {
+    /// Type of the term in the context of the types of the enclosing parameters, the innermost last
+    fn type_in(&self, context: &mut Vec<(String, Type)>) -> Result<Type, TypeError> {
+        match &self.term {
+            // A variable has the type of the innermost parameter with its name
+            Term::Var(s) => context
+                .iter()
+                .rev()
+                .find(|(name, _)| name == s)
+                .map(|(_, ty)| ty.clone())
+                .ok_or_else(|| TypeError::UnboundVariable(Box::new(self.clone()))),
+            // An abstraction has the arrow type from its parameter to its body
+            Term::Abstr(param, body) => match &param.annotation {
+                Some(ty) => {
+                    let body = body.type_in_scope(param, ty.clone(), context)?;
+                    Ok(Type::arrow(ty.clone(), body))
+                }
+                None => Err(TypeError::Unannotated(param.clone())),
+            },
+            Term::Apply(f, arg) => match &f.term {
+                // An unannotated parameter of an abstraction applied right away, e.g. from
+                // a let binding, takes the type of the argument
+                Term::Abstr(param, body) if param.annotation.is_none() => {
+                    let ty = arg.type_in(context)?;
+                    body.type_in_scope(param, ty, context)
+                }
+                _ => match f.type_in(context)? {
+                    Type::Arrow(from, to) => {
+                        let found = arg.type_in(context)?;
+                        match found.alpha_equivalent(&from) {
+                            true => Ok(*to),
+                            false => Err(TypeError::Mismatch {
+                                term: arg.clone(),
+                                expected: *from,
+                                found,
+                            }),
+                        }
+                    }
+                    ty => Err(TypeError::NotAFunction(f.clone(), ty)),
+                },
+            },
+        }
+    }
+
+    /// Type of the term with the parameter of the given type added to the context
+    fn type_in_scope(
+        &self,
+        param: &AST,
+        ty: Type,
+        context: &mut Vec<(String, Type)>,
+    ) -> Result<Type, TypeError> {
+        let name = match &param.term {
+            Term::Var(s) => s.clone(),
+            _ => panic!("Abstraction parameter must be a variable"),
+        };
+        context.push((name, ty));
+        let result = self.type_in(context);
+        context.pop();
+        result
+    }
+}
+
+impl TypeCheck for AST {
+    /// Type of a closed term. Every parameter needs an annotation, unless it is bound
+    /// to an argument right away
+    fn type_of(&self) -> Result<Type, TypeError> {
+        self.type_in(&mut Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TypeCheck, TypeError};
+    use crate::ast::AST;
+
+    fn type_of(input: &str) -> Result<String, TypeError> {
+        AST::eval(input).unwrap().type_of().map(|ty| ty.to_string())
+    }
+
+    #[test]
+    fn annotated_terms() {
+        assert_eq!(
+            type_of("$f: A -> B, x: A -> f x").unwrap(),
+            "(A -> B) -> A -> B"
+        );
+        assert!(matches!(
+            type_of("$f: A -> B, x: B -> f x"),
+            Err(TypeError::Mismatch { .. })
+        ));
+        assert!(matches!(type_of("$x -> x"), Err(TypeError::Unannotated(_))));
+    }
+
+    #[test]
+    fn quantified_names_do_not_matter() {
+        assert_eq!(
+            type_of("$f: (\\/a. a -> a) -> A, g: \\/b. b -> b -> f g").unwrap(),
+            "((\\/a. a -> a) -> A) -> (\\/b. b -> b) -> A"
+        );
+    }
+}
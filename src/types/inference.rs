@@ -0,0 +1,313 @@
+use crate::ast::Term;
+use crate::ast::AST;
+use crate::types::{Type, TypeError};
+use std::collections::HashMap;
+
+/// Type represents a polymorphic type, i.e. a type that holds for every type of its variables.
+/// Names bound by let get one, so id in let id = $x -> x in id id is used at two types
+#[derive(Debug, Clone)]
+struct Scheme {
+    vars: Vec<usize>,
+    ty: Type,
+}
+
+impl Scheme {
+    /// A type without variables of its own, e.g. of a lambda parameter
+    fn mono(ty: Type) -> Self {
+        Scheme {
+            vars: Vec::new(),
+            ty,
+        }
+    }
+}
+
+/// Type represents the reason two types cannot be unified
+///  - *Clash*: the types have a different shape, e.g. A and B -> C
+///  - *Occurs*: a variable would have to be replaced by a type containing it
+enum UnifyError {
+    Clash,
+    Occurs(usize, Type),
+}
+
+/// Type represents the state of the inference, i.e. the substitution of type variables found
+/// so far and the counter of fresh variables
+#[derive(Default)]
+struct Inference {
+    substitution: HashMap<usize, Type>,
+    next: usize,
+}
+
+impl Inference {
+    fn fresh(&mut self) -> Type {
+        self.next += 1;
+        Type::Var(self.next - 1)
+    }
+
+    /// Apply the substitution to the type until no substituted variable is left
+    fn resolve(&self, ty: &Type) -> Type {
+        match ty {
+            Type::Base(_) => ty.clone(),
+            Type::Var(n) => match self.substitution.get(n) {
+                Some(ty) => self.resolve(ty),
+                None => ty.clone(),
+            },
+            Type::Arrow(from, to) => Type::arrow(self.resolve(from), self.resolve(to)),
+            Type::Forall(name, body) => Type::forall(name.clone(), self.resolve(body)),
+        }
+    }
+
+    /// Extend the substitution so the two types become equal
+    fn unify(&mut self, left: &Type, right: &Type) -> Result<(), UnifyError> {
+        match (self.resolve(left), self.resolve(right)) {
+            (Type::Var(x), Type::Var(y)) if x == y => Ok(()),
+            // A variable cannot be replaced by a type that contains it, it would be infinite
+            (Type::Var(x), ty) | (ty, Type::Var(x)) => {
+                let mut vars = Vec::new();
+                ty.vars(&mut vars);
+                match vars.contains(&x) {
+                    true => Err(UnifyError::Occurs(x, ty)),
+                    false => {
+                        self.substitution.insert(x, ty);
+                        Ok(())
+                    }
+                }
+            }
+            (Type::Base(a), Type::Base(b)) if a == b => Ok(()),
+            // Polymorphic types only come from annotations, so they have to match up to the
+            // names of their quantified variables
+            (left @ Type::Forall(_, _), right @ Type::Forall(_, _))
+                if left.alpha_equivalent(&right) =>
+            {
+                Ok(())
+            }
+            (Type::Arrow(a, b), Type::Arrow(c, d)) => {
+                self.unify(&a, &c)?;
+                self.unify(&b, &d)
+            }
+            _ => Err(UnifyError::Clash),
+        }
+    }
+
+    /// Unify the type the term is expected to have with the type found for it
+    fn expect(&mut self, term: &AST, expected: &Type, found: &Type) -> Result<(), TypeError> {
+        self.unify(expected, found).map_err(|err| match err {
+            UnifyError::Clash => TypeError::Mismatch {
+                term: Box::new(term.clone()),
+                expected: self.resolve(expected),
+                found: self.resolve(found),
+            },
+            UnifyError::Occurs(var, ty) => TypeError::Infinite {
+                term: Box::new(term.clone()),
+                var: Type::Var(var),
+                ty,
+            },
+        })
+    }
+
+    /// Replace the variables of the scheme with fresh ones
+    fn instantiate(&mut self, scheme: &Scheme) -> Type {
+        let fresh: HashMap<usize, Type> =
+            scheme.vars.iter().map(|var| (*var, self.fresh())).collect();
+        let ty = self.resolve(&scheme.ty);
+        substitute(&ty, &fresh)
+    }
+
+    /// Turn the variables of the type that are not bound in the context into variables of
+    /// the scheme
+    fn generalize(&self, ty: &Type, context: &[(String, Scheme)]) -> Scheme {
+        let mut bound = Vec::new();
+        for (_, scheme) in context {
+            let mut vars = Vec::new();
+            self.resolve(&scheme.ty).vars(&mut vars);
+            bound.extend(vars.into_iter().filter(|var| !scheme.vars.contains(var)));
+        }
+        let ty = self.resolve(ty);
+        let mut vars = Vec::new();
+        ty.vars(&mut vars);
+        vars.retain(|var| !bound.contains(var));
+        Scheme { vars, ty }
+    }
+
+    /// Infer the type of the term in the context, the innermost binding last
+    fn infer(&mut self, ast: &AST, context: &mut Vec<(String, Scheme)>) -> Result<Type, TypeError> {
+        match &ast.term {
+            // A variable gets a fresh instance of the type of its binding
+            Term::Var(s) => match context.iter().rev().find(|(name, _)| name == s) {
+                Some((_, scheme)) => {
+                    let scheme = scheme.clone();
+                    Ok(self.instantiate(&scheme))
+                }
+                None => Err(TypeError::UnboundVariable(Box::new(ast.clone()))),
+            },
+            // The parameter has its annotation or a fresh variable as its type
+            Term::Abstr(param, body) => {
+                let ty = match &param.annotation {
+                    Some(ty) => ty.clone(),
+                    None => self.fresh(),
+                };
+                let body = self.infer_in_scope(body, param, Scheme::mono(ty.clone()), context)?;
+                Ok(Type::arrow(ty, body))
+            }
+            Term::Apply(f, arg) => match &f.term {
+                // A let binding, its parameter is polymorphic in the body. The parser turns
+                // let x = a in b into ($x -> b) a and marks it, a redex written as such is not one
+                Term::Abstr(param, body) if ast.is_let => {
+                    let ty = self.infer(arg, context)?;
+                    if let Some(annotation) = &param.annotation {
+                        self.expect(arg, annotation, &ty)?;
+                    }
+                    let scheme = self.generalize(&ty, context);
+                    self.infer_in_scope(body, param, scheme, context)
+                }
+                _ => {
+                    let function = self.infer(f, context)?;
+                    let ty = self.infer(arg, context)?;
+                    match self.resolve(&function) {
+                        Type::Arrow(from, to) => {
+                            self.expect(arg, &from, &ty)?;
+                            Ok(*to)
+                        }
+                        // The type is not known yet, so it becomes a function type
+                        Type::Var(_) => {
+                            let to = self.fresh();
+                            self.expect(ast, &function, &Type::arrow(ty, to.clone()))?;
+                            Ok(to)
+                        }
+                        function => Err(TypeError::NotAFunction(f.clone(), function)),
+                    }
+                }
+            },
+        }
+    }
+
+    /// Infer the type of the term with the parameter bound to the scheme
+    fn infer_in_scope(
+        &mut self,
+        ast: &AST,
+        param: &AST,
+        scheme: Scheme,
+        context: &mut Vec<(String, Scheme)>,
+    ) -> Result<Type, TypeError> {
+        let name = match &param.term {
+            Term::Var(s) => s.clone(),
+            _ => panic!("Abstraction parameter must be a variable"),
+        };
+        context.push((name, scheme));
+        let result = self.infer(ast, context);
+        context.pop();
+        result
+    }
+}
+
+/// Replace the type variables in the map
+fn substitute(ty: &Type, map: &HashMap<usize, Type>) -> Type {
+    match ty {
+        Type::Base(_) => ty.clone(),
+        Type::Var(n) => map.get(n).cloned().unwrap_or_else(|| ty.clone()),
+        Type::Arrow(from, to) => Type::arrow(substitute(from, map), substitute(to, map)),
+        Type::Forall(name, body) => Type::forall(name.clone(), substitute(body, map)),
+    }
+}
+
+pub trait TypeInference {
+    fn infer_type(&self) -> Result<Type, TypeError>;
+}
+
+impl TypeInference for AST {
+    /// The most general type of a closed term by the Hindley-Milner inference. Annotated
+    /// parameters keep their types and let bindings are polymorphic. Only a let is, so
+    /// let x = $y -> y in x x has a type but ($x -> x x) ($y -> y) does not
+    fn infer_type(&self) -> Result<Type, TypeError> {
+        let mut inference = Inference::default();
+        let ty = inference
+            .infer(self, &mut Vec::new())
+            .map_err(TypeError::pretty)?;
+        Ok(inference.resolve(&ty).pretty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TypeInference;
+    use crate::ast::AST;
+    use crate::environment::Environment;
+    use crate::types::TypeError;
+
+    fn infer(input: &str) -> Result<String, TypeError> {
+        AST::eval(input)
+            .unwrap()
+            .infer_type()
+            .map(|ty| ty.to_string())
+    }
+
+    #[test]
+    fn most_general_type() {
+        assert_eq!(infer("$x -> x").unwrap(), "a -> a");
+        assert_eq!(
+            infer("$f, g, x -> f (g x)").unwrap(),
+            "(a -> b) -> (c -> a) -> c -> b"
+        );
+    }
+
+    #[test]
+    fn occurs_check() {
+        assert!(matches!(
+            infer("$x -> x x"),
+            Err(TypeError::Infinite { .. })
+        ));
+        assert!(matches!(
+            infer("$f -> ($x -> f (x x)) ($x -> f (x x))"),
+            Err(TypeError::Infinite { .. })
+        ));
+    }
+
+    #[test]
+    fn let_polymorphism() {
+        assert_eq!(infer("let id = $x -> x in id id").unwrap(), "a -> a");
+        assert_eq!(
+            infer("let id = $x -> x in $a: A, b: B -> ($p, q -> p) (id a) (id b)").unwrap(),
+            "A -> B -> A"
+        );
+        // A redex written as such is not a let, so its parameter is monomorphic
+        assert!(matches!(
+            infer("($x -> x x) ($y -> y)"),
+            Err(TypeError::Infinite { .. })
+        ));
+        assert_eq!(infer("let x = $y -> y in x x").unwrap(), "a -> a");
+        // The let is still one after the definitions are substituted into it
+        let env = Environment::new().with("i".to_string(), AST::eval("$x -> x").unwrap());
+        let ast = env.resolve(AST::eval("let f = i in f f").unwrap());
+        assert_eq!(ast.infer_type().unwrap().to_string(), "a -> a");
+    }
+
+    #[test]
+    fn parameters_are_monomorphic() {
+        assert!(matches!(
+            infer("$f -> f f"),
+            Err(TypeError::Infinite { .. })
+        ));
+        // The argument has the type of a parameter of the context, so it is not generalized
+        assert!(matches!(
+            infer("$f -> ($g -> g g) f"),
+            Err(TypeError::Infinite { .. })
+        ));
+    }
+
+    #[test]
+    fn polymorphic_annotations_unify_up_to_bound_names() {
+        assert_eq!(
+            infer("$f: (\\/a. a -> a) -> A, g: \\/b. b -> b -> f g").unwrap(),
+            "((\\/a. a -> a) -> A) -> (\\/b. b -> b) -> A"
+        );
+        assert!(matches!(
+            infer("$f: (\\/a. a -> a) -> A, g: \\/b. b -> A -> f g"),
+            Err(TypeError::Mismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn variables_skip_base_names() {
+        assert_eq!(infer("$x: a, y -> y").unwrap(), "a -> b -> b");
+        assert_eq!(infer("$x: b, y -> y").unwrap(), "b -> a -> a");
+    }
+}
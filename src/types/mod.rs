@@ -0,0 +1,8 @@
+//! This module handles the types of the lambda calculus, their checking and their inference
+pub mod checker;
+pub mod inference;
+pub mod types;
+
+pub use self::checker::{TypeCheck, TypeError};
+pub use self::inference::TypeInference;
+pub use self::types::Type;
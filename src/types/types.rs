@@ -0,0 +1,146 @@
+use std::fmt;
+
+/// Type represents a type of the lambda calculus
+///  - *Base*: a type given by its name, e.g. A. Under a quantifier of the same name it is
+///    the variable the quantifier binds
+///  - *Var*: a type variable of the inference, which stands for any type. It is printed as
+///    a letter, i.e. 0 => a, so the letters of base types are skipped when numbering them
+///  - *Arrow*: the type of functions from the first type to the second one, e.g. A -> B
+///  - *Forall*: a polymorphic type of System F, e.g. \/a. a -> a
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Type {
+    Base(String),
+    Var(usize),
+    Arrow(Box<Type>, Box<Type>),
+    Forall(String, Box<Type>),
+}
+
+impl Type {
+    pub fn arrow(from: Type, to: Type) -> Type {
+        Type::Arrow(Box::new(from), Box::new(to))
+    }
+
+    pub fn forall(name: String, body: Type) -> Type {
+        Type::Forall(name, Box::new(body))
+    }
+
+    /// Collect the type variables in the order they appear, without duplicates
+    pub fn vars(&self, vars: &mut Vec<usize>) {
+        match self {
+            Type::Base(_) => (),
+            Type::Var(n) if vars.contains(n) => (),
+            Type::Var(n) => vars.push(*n),
+            Type::Arrow(from, to) => {
+                from.vars(vars);
+                to.vars(vars);
+            }
+            Type::Forall(_, body) => body.vars(vars),
+        }
+    }
+
+    /// Collect the names of the base types, including the names the quantifiers bind
+    pub fn bases(&self, bases: &mut Vec<String>) {
+        match self {
+            Type::Base(name) if bases.contains(name) => (),
+            Type::Base(name) => bases.push(name.clone()),
+            Type::Var(_) => (),
+            Type::Arrow(from, to) => {
+                from.bases(bases);
+                to.bases(bases);
+            }
+            Type::Forall(name, body) => {
+                if !bases.contains(name) {
+                    bases.push(name.clone());
+                }
+                body.bases(bases);
+            }
+        }
+    }
+
+    /// Number the type variables by their first appearance, skipping the numbers that would
+    /// be printed as one of the taken names. The names are shared, so the types printed
+    /// together in one message keep their variables apart
+    pub fn rename(&self, names: &mut Vec<(usize, usize)>, taken: &[String]) -> Type {
+        match self {
+            Type::Base(_) => self.clone(),
+            Type::Var(n) => match names.iter().find(|(var, _)| var == n) {
+                Some((_, name)) => Type::Var(*name),
+                None => {
+                    let mut name = names.last().map_or(0, |(_, name)| name + 1);
+                    while taken.contains(&Type::Var(name).to_string()) {
+                        name += 1;
+                    }
+                    names.push((*n, name));
+                    Type::Var(name)
+                }
+            },
+            Type::Arrow(from, to) => {
+                let from = from.rename(names, taken);
+                Type::arrow(from, to.rename(names, taken))
+            }
+            Type::Forall(name, body) => Type::forall(name.clone(), body.rename(names, taken)),
+        }
+    }
+
+    /// Rename the type variables to a, b, c, ... in the order they appear, skipping the
+    /// letters of base types, i.e. a -> 0 becomes a -> b
+    pub fn pretty(&self) -> Type {
+        let mut bases = Vec::new();
+        self.bases(&mut bases);
+        self.rename(&mut Vec::new(), &bases)
+    }
+
+    /// Whether the types are equal up to the names of the quantified variables, i.e.
+    /// \/a. a -> a and \/b. b -> b are the same type
+    pub fn alpha_equivalent(&self, other: &Type) -> bool {
+        self.equivalent_in(other, &mut Vec::new())
+    }
+
+    /// Compare the types with the pairs of variables bound by the enclosing quantifiers
+    fn equivalent_in(&self, other: &Type, bound: &mut Vec<(String, String)>) -> bool {
+        match (self, other) {
+            // The innermost quantifier binding either name decides
+            (Type::Base(a), Type::Base(b)) => {
+                match bound
+                    .iter()
+                    .rev()
+                    .find(|(left, right)| left == a || right == b)
+                {
+                    Some((left, right)) => left == a && right == b,
+                    None => a == b,
+                }
+            }
+            (Type::Var(x), Type::Var(y)) => x == y,
+            (Type::Arrow(a, b), Type::Arrow(c, d)) => {
+                a.equivalent_in(c, bound) && b.equivalent_in(d, bound)
+            }
+            (Type::Forall(a, left), Type::Forall(b, right)) => {
+                bound.push((a.clone(), b.clone()));
+                let result = left.equivalent_in(right, bound);
+                bound.pop();
+                result
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    /// Print the type with the arrow associating to the right, i.e. (A -> B) -> A -> B
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Base(name) => write!(f, "{}", name),
+            // After z the letters start again with a number, i.e. a1, b1, ...
+            Type::Var(n) => match n / 26 {
+                0 => write!(f, "{}", (b'a' + (n % 26) as u8) as char),
+                round => write!(f, "{}{}", (b'a' + (n % 26) as u8) as char, round),
+            },
+            // A quantifier extends as far to the right as possible, the same as an arrow
+            Type::Arrow(from, to) => match from.as_ref() {
+                Type::Arrow(_, _) | Type::Forall(_, _) => write!(f, "({}) -> {}", from, to),
+                _ => write!(f, "{} -> {}", from, to),
+            },
+            Type::Forall(name, body) => write!(f, "\\/{}. {}", name, body),
+        }
+    }
+}
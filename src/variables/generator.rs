@@ -1,4 +1,5 @@
 /// Type represents a generator of variable names for the purpose of renaming
+#[derive(Clone)]
 pub struct VarGen {
     current: Vec<char>,
 }
@@ -77,11 +77,12 @@ impl VarSet {
 
     /// Return the union of two sets
     pub fn union(self, other: VarSet) -> VarSet {
-        let inner = other
-            .inner
-            .into_iter()
-            .chain(self.inner.into_iter())
-            .collect();
+        // The smaller set is added to the larger one, which keeps its allocation
+        let (mut inner, smaller) = match self.inner.len() >= other.inner.len() {
+            true => (self.inner, other.inner),
+            false => (other.inner, self.inner),
+        };
+        inner.extend(smaller);
         VarSet { inner }
     }
 
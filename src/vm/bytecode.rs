@@ -0,0 +1,117 @@
+use crate::ast::AST;
+use crate::debruijn::DeBruijn;
+use std::fmt;
+use std::rc::Rc;
+
+/// Type represents a single instruction of the virtual machine
+///  - *Access*: continue with the closure bound to the de Bruijn index
+///  - *Grab*: bind the argument on top of the stack and continue with the next instruction,
+///    or stop if there is no argument
+///  - *Push*: push the code at the address as an argument and continue with the next instruction
+///  - *Free*: stop at the free variable with the index into the names of the program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Access(usize),
+    Grab,
+    Push(usize),
+    Free(usize),
+}
+
+/// Type represents a compiled term. The code of the whole term starts at address 0,
+/// every argument is a block of its own that ends with *Access* or *Free*
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub code: Vec<Instruction>,
+    pub names: Vec<String>,
+}
+
+impl Program {
+    /// Compile a term into bytecode
+    pub fn compile(ast: &AST) -> Self {
+        let mut program = Program {
+            code: Vec::new(),
+            names: Vec::new(),
+        };
+        let mut blocks = vec![(Rc::new(ast.to_de_bruijn()), None)];
+        // Blocks are laid out one after another, an argument is compiled when its block is reached
+        while let Some((term, push)) = blocks.pop() {
+            if let Some(push) = push {
+                program.code[push] = Instruction::Push(program.code.len());
+            }
+            program.compile_block(&term, &mut blocks);
+        }
+        program
+    }
+
+    /// Compile the spine of a term, leaving the arguments for later blocks
+    fn compile_block(&mut self, term: &DeBruijn, blocks: &mut Vec<(Rc<DeBruijn>, Option<usize>)>) {
+        let mut term = term;
+        loop {
+            match term {
+                DeBruijn::Bound(index) => return self.code.push(Instruction::Access(*index)),
+                DeBruijn::Free(s) => {
+                    let index = match self.names.iter().position(|name| name == s) {
+                        Some(index) => index,
+                        None => {
+                            self.names.push(s.clone());
+                            self.names.len() - 1
+                        }
+                    };
+                    return self.code.push(Instruction::Free(index));
+                }
+                DeBruijn::Abstr(body) => {
+                    self.code.push(Instruction::Grab);
+                    term = body;
+                }
+                // The address of the argument is patched in once its block is compiled
+                DeBruijn::Apply(f, arg) => {
+                    blocks.push((arg.clone(), Some(self.code.len())));
+                    self.code.push(Instruction::Push(0));
+                    term = f;
+                }
+            }
+        }
+    }
+
+    /// Turn the code starting at the address back into a term
+    pub fn decompile(&self, address: usize) -> DeBruijn {
+        match self.code[address] {
+            Instruction::Access(index) => DeBruijn::Bound(index),
+            Instruction::Free(index) => DeBruijn::Free(self.names[index].clone()),
+            Instruction::Grab => DeBruijn::Abstr(Rc::new(self.decompile(address + 1))),
+            Instruction::Push(arg) => DeBruijn::Apply(
+                Rc::new(self.decompile(address + 1)),
+                Rc::new(self.decompile(arg)),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Access(index) => write!(f, "ACCESS {}", index),
+            Instruction::Grab => write!(f, "GRAB"),
+            Instruction::Push(address) => write!(f, "PUSH {}", address),
+            Instruction::Free(index) => write!(f, "FREE {}", index),
+        }
+    }
+}
+
+impl fmt::Display for Program {
+    /// Print every instruction on a line of its own with its address.
+    /// Free variables are shown by their names
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (address, instruction) in self.code.iter().enumerate() {
+            match instruction {
+                Instruction::Free(index) => writeln!(
+                    f,
+                    "{:>4}  {}    ; {}",
+                    address, instruction, self.names[*index]
+                )?,
+                _ => writeln!(f, "{:>4}  {}", address, instruction)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+use crate::ast::AST;
+use crate::beta::{ReductionLimits, ReductionResult};
+use crate::debruijn::DeBruijn;
+use crate::variables::VarGen;
+use crate::vm::{Instruction, Program};
+use std::rc::Rc;
+use std::time::Instant;
+
+/// Type represents code together with the environment that binds its indices.
+/// The environment is an index of a heap cell, *None* is the empty one
+#[derive(Debug, Clone, Copy)]
+struct Closure {
+    address: usize,
+    env: Option<usize>,
+}
+
+/// Type represents a cell of the heap, one binding of an environment
+#[derive(Debug, Clone, Copy)]
+struct Cell {
+    closure: Closure,
+    next: Option<usize>,
+}
+
+/// Type represents the counters of a run of the virtual machine
+///  - *instructions*: the number of instructions executed
+///  - *steps*: the number of beta reductions, i.e. arguments grabbed
+///  - *peak_stack*: the largest number of arguments on the stack at once
+///  - *heap_cells*: the number of cells allocated on the heap
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stats {
+    pub instructions: usize,
+    pub steps: usize,
+    pub peak_stack: usize,
+    pub heap_cells: usize,
+}
+
+/// Type represents the virtual machine running a program. The heap is an arena
+/// that only grows, so the environments live as long as the machine
+pub struct Vm<'a> {
+    program: &'a Program,
+    closure: Closure,
+    stack: Vec<Closure>,
+    heap: Vec<Cell>,
+    stats: Stats,
+}
+
+impl<'a> Vm<'a> {
+    /// Load a program into a new machine
+    pub fn new(program: &'a Program) -> Self {
+        Vm {
+            program,
+            closure: Closure {
+                address: 0,
+                env: None,
+            },
+            stack: Vec::new(),
+            heap: Vec::new(),
+            stats: Stats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Look up the closure bound to the index in the environment
+    fn lookup(&self, env: Option<usize>, index: usize) -> Closure {
+        let mut cell = &self.heap[env.expect("Unbound de Bruijn index")];
+        for _ in 0..index {
+            cell = &self.heap[cell.next.expect("Unbound de Bruijn index")];
+        }
+        cell.closure
+    }
+
+    /// Execute instructions up to the next beta reduction. Returns false when the machine stops,
+    /// i.e. at *Grab* with an empty stack or at a free variable
+    pub fn step(&mut self) -> bool {
+        loop {
+            self.stats.instructions += 1;
+            let Closure { address, env } = self.closure;
+            match self.program.code[address] {
+                Instruction::Access(index) => self.closure = self.lookup(env, index),
+                Instruction::Push(arg) => {
+                    // A variable is pushed as the closure it is bound to, so chains of
+                    // variables bound to variables never form
+                    let closure = match self.program.code[arg] {
+                        Instruction::Access(index) => self.lookup(env, index),
+                        _ => Closure { address: arg, env },
+                    };
+                    self.stack.push(closure);
+                    self.stats.peak_stack = self.stats.peak_stack.max(self.stack.len());
+                    self.closure.address += 1;
+                }
+                Instruction::Grab => {
+                    return match self.stack.pop() {
+                        Some(closure) => {
+                            self.heap.push(Cell { closure, next: env });
+                            self.stats.heap_cells = self.heap.len();
+                            self.stats.steps += 1;
+                            self.closure = Closure {
+                                address: address + 1,
+                                env: Some(self.heap.len() - 1),
+                            };
+                            true
+                        }
+                        None => false,
+                    }
+                }
+                Instruction::Free(_) => return false,
+            }
+        }
+    }
+
+    /// Read a closure back into a term, substituting its environment into the code
+    fn unload(&self, closure: Closure) -> DeBruijn {
+        self.unload_in(&self.program.decompile(closure.address), closure.env, 0)
+    }
+
+    /// Unloading under the given number of binders of the term itself
+    fn unload_in(&self, term: &DeBruijn, env: Option<usize>, depth: usize) -> DeBruijn {
+        match term {
+            DeBruijn::Bound(index) if *index < depth => DeBruijn::Bound(*index),
+            DeBruijn::Bound(index) => self
+                .unload(self.lookup(env, index - depth))
+                .shift(depth as isize, 0),
+            DeBruijn::Free(s) => DeBruijn::Free(s.clone()),
+            DeBruijn::Abstr(body) => DeBruijn::Abstr(Rc::new(self.unload_in(body, env, depth + 1))),
+            DeBruijn::Apply(f, arg) => DeBruijn::Apply(
+                Rc::new(self.unload_in(f, env, depth)),
+                Rc::new(self.unload_in(arg, env, depth)),
+            ),
+        }
+    }
+
+    /// Read the current state back into a term, the closure applied to the arguments on the stack
+    pub fn term(&self) -> AST {
+        self.stack
+            .iter()
+            .rev()
+            .fold(self.unload(self.closure), |term, arg| {
+                DeBruijn::Apply(Rc::new(term), Rc::new(self.unload(*arg)))
+            })
+            .to_named(&VarGen::new())
+    }
+
+    /// Run the program to the weak head normal form or until a limit is hit
+    pub fn run(&mut self, limits: &ReductionLimits) -> ReductionResult {
+        loop {
+            if limits.fuel.is_some_and(|fuel| self.stats.steps >= fuel) {
+                return ReductionResult::OutOfFuel(self.term(), self.stats.steps);
+            }
+            if limits
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+            {
+                return ReductionResult::TimedOut(self.term());
+            }
+            if !self.step() {
+                return ReductionResult::weak_head(self.term());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Vm;
+    use crate::ast::AST;
+    use crate::beta::{ReductionLimits, ReductionResult};
+    use crate::vm::Program;
+
+    fn run(input: &str) -> ReductionResult {
+        let limits = ReductionLimits {
+            fuel: Some(1000),
+            deadline: None,
+        };
+        Vm::new(&Program::compile(&AST::eval(input).unwrap())).run(&limits)
+    }
+
+    #[test]
+    fn normal_form() {
+        match run("($x, y -> y x) a ($z -> z)") {
+            ReductionResult::Normalized(ast) => assert_eq!(ast, AST::eval("a").unwrap()),
+            result => panic!("Expected a normal form, found {}", result),
+        }
+    }
+
+    #[test]
+    fn weak_head_is_not_normalized() {
+        match run("($x -> x) ($y -> ($z -> z) y)") {
+            ReductionResult::WeakHead(ast) => {
+                assert_eq!(ast, AST::eval("$y -> ($z -> z) y").unwrap())
+            }
+            result => panic!("Expected a weak head normal form, found {}", result),
+        }
+    }
+}
@@ -0,0 +1,7 @@
+//! This module compiles terms into bytecode for a Krivine machine and runs it on a small
+//! virtual machine with its own stack and heap
+pub mod bytecode;
+pub mod machine;
+
+pub use self::bytecode::{Instruction, Program};
+pub use self::machine::Vm;